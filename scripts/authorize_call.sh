@@ -27,18 +27,20 @@ REPO_ROOT="$(cd "$(dirname "${BASH_SOURCE[0]}")"/.. && pwd)"
 
 ARGS=(
   --function "$FUNCTION_NAME"
-  --private-key "$PRIVATE_KEY"
 )
 
+# The network applies to local program files too: it selects the key format, the imports and
+# the API the fee is estimated against.
+if [[ -n "${AUTHORIZE_NETWORK:-}" ]]; then
+  ARGS+=(--network "${AUTHORIZE_NETWORK}")
+fi
+
 if [[ -f "$PROGRAM_SPEC" ]]; then
   ARGS+=(--program-file "$PROGRAM_SPEC")
 elif [[ -f "$REPO_ROOT/$PROGRAM_SPEC" ]]; then
   ARGS+=(--program-file "$REPO_ROOT/$PROGRAM_SPEC")
 else
   ARGS+=(--program-id "$PROGRAM_SPEC")
-  if [[ -n "${AUTHORIZE_NETWORK:-}" ]]; then
-    ARGS+=(--network "${AUTHORIZE_NETWORK}")
-  fi
   if [[ -n "${AUTHORIZE_EDITION:-}" ]]; then
     ARGS+=(--edition "${AUTHORIZE_EDITION}")
   fi
@@ -51,14 +53,24 @@ for value in "$@"; do
   ARGS+=(--input "$value")
 done
 
+if [[ -n "${AUTHORIZE_PROVER_URL:-}" ]]; then
+  ARGS+=(--prover-url "${AUTHORIZE_PROVER_URL}")
+fi
+
+if [[ "${AUTHORIZE_BROADCAST:-0}" != 0 ]]; then
+  ARGS+=(--broadcast)
+fi
+
 if [[ "${PRINT_ACCOUNT:-0}" != 0 ]]; then
   ARGS+=(--print-account)
 fi
 
-CARGO_CMD=(cargo run --manifest-path "$REPO_ROOT/Cargo.toml" --bin authorize --quiet --)
+CARGO_CMD=(cargo run --manifest-path "$REPO_ROOT/Cargo.toml" --bin authorize --quiet -- authorize)
 
 if [[ "${AUTHORIZE_RELEASE:-0}" != 0 ]]; then
-  CARGO_CMD=(cargo run --manifest-path "$REPO_ROOT/Cargo.toml" --bin authorize --release --)
+  CARGO_CMD=(cargo run --manifest-path "$REPO_ROOT/Cargo.toml" --bin authorize --release -- authorize)
 fi
 
-"${CARGO_CMD[@]}" "${ARGS[@]}"
+# Hand the key over through the environment so it does not show up in the authorize
+# process's command line.
+ALEO_PRIVATE_KEY="$PRIVATE_KEY" "${CARGO_CMD[@]}" "${ARGS[@]}"
@@ -1,154 +1,153 @@
-use clap::{ArgGroup, Parser};
-use remote_prover::{CurrentAleo, CurrentNetwork, ProverConfig};
-use reqwest::blocking::Client;
-use reqwest::Url;
-use snarkvm::prelude::{Address, Identifier, PrivateKey, Program, ProgramID, ViewKey};
-use snarkvm::synthesizer::Process;
-use std::{collections::HashSet, fs, io, path::PathBuf, str::FromStr, time::Duration};
-
-struct RemoteFetcher {
-    client: Client,
-    base_url: Url,
-}
-
-impl RemoteFetcher {
-    fn new(base_url: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let url = Url::parse(base_url)
-            .map_err(|e| with_context(format!("invalid API base '{base_url}'"), e))?;
-
-        let client = Client::builder()
-            .timeout(Duration::from_secs(20))
-            .build()
-            .map_err(|e| with_context("failed to build HTTP client", e))?;
-
-        Ok(Self {
-            client,
-            base_url: url,
-        })
-    }
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use rand::rngs::ThreadRng;
+use remote_prover::{
+    LocalDirSource, ProgramResolver, ProveRequest, ProverClient, ProverConfig, ResolvedProgram,
+    RestSource, NETWORK,
+};
+use snarkvm::ledger::block::Output;
+use snarkvm::ledger::query::{Query, QueryTrait};
+use snarkvm::ledger::store::helpers::memory::BlockMemory;
+use snarkvm::circuit::{Aleo, AleoCanaryV0, AleoTestnetV0, AleoV0};
+use snarkvm::prelude::{
+    Address, Authorization, CanaryV0, Ciphertext, Execution, Identifier, LiteralType,
+    MainnetV0, Network, Plaintext, PlaintextType, PrivateKey, Program, ProgramID, Record,
+    TestnetV0, Transition, Value, ValueType, ViewKey,
+};
+use snarkvm::synthesizer::{
+    process::{execution_cost, execution_storage_cost},
+    Process,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env, fs,
+    io::{self, BufRead, IsTerminal},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use zeroize::Zeroizing;
 
-    fn fetch_latest_edition(
-        &self,
-        program_id: &str,
-    ) -> Result<Option<u16>, Box<dyn std::error::Error>> {
-        let mut url = self.base_url.clone();
-        {
-            let mut segments = url
-                .path_segments_mut()
-                .map_err(|_| boxed_err("API base URL must be absolute"))?;
-            segments.pop_if_empty();
-            segments.push("program");
-            segments.push(program_id);
-            segments.push("latest_edition");
-        }
+/// Upper bounds on the size of the execution proof, which a local run without a proof cannot
+/// measure. A Varuna batch proof is a fixed part plus commitments and evaluations for every
+/// transition's circuit. The bounds are added to the size of the proof-less execution before
+/// its storage cost is computed, which grows quadratically past the network's size threshold.
+/// Both are rounded up, so the estimate errs towards overpaying; `--base-fee` overrides it.
+const PROOF_FIXED_BYTES: u64 = 1_024;
+const PROOF_BYTES_PER_TRANSITION: u64 = 512;
 
-        let response = self
-            .client
-            .get(url.clone())
-            .header("Accept", "application/json")
-            .send()
-            .map_err(|e| {
-                with_context(
-                    format!("failed to fetch latest edition for '{program_id}'"),
-                    e,
-                )
-            })?;
+/// Environment variable consulted for the private key when no key option is given.
+const PRIVATE_KEY_ENV: &str = "ALEO_PRIVATE_KEY";
 
-        if !response.status().is_success() {
-            // If 404, the program may not have editions yet
-            if response.status().as_u16() == 404 {
-                return Ok(None);
-            }
-            return Err(boxed_err(format!(
-                "latest edition request for '{}' failed with status {}",
-                program_id,
-                response.status()
-            )));
-        }
+/// Account and authorization tooling for the remote prover.
+#[derive(Parser, Debug)]
+#[command(
+    author,
+    version,
+    about = "Aleo account and authorization tooling for the remote prover",
+    long_about = None,
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
-        let body = response
-            .text()
-            .map_err(|e| with_context("failed to read latest edition response", e))?;
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate or inspect Aleo accounts
+    #[command(subcommand)]
+    Account(AccountCommand),
+    /// Generate an Aleo authorization string for a program execution
+    Authorize(Box<AuthorizeArgs>),
+}
 
-        let edition: u16 = body.trim().parse().map_err(|e| {
-            with_context(
-                format!("failed to parse edition number for '{program_id}'"),
-                e,
-            )
-        })?;
+#[derive(Subcommand, Debug)]
+enum AccountCommand {
+    /// Generate a new private key and print it with its view key and address
+    New(AccountNewArgs),
+    /// Print the view key and address of an existing private key
+    Inspect(AccountInspectArgs),
+}
 
-        Ok(Some(edition))
-    }
-
-    fn fetch_program(
-        &self,
-        program_id: &str,
-        edition: Option<u16>,
-    ) -> Result<Program<CurrentNetwork>, Box<dyn std::error::Error>> {
-        let mut url = self.base_url.clone();
-        {
-            let mut segments = url
-                .path_segments_mut()
-                .map_err(|_| boxed_err("API base URL must be absolute"))?;
-            segments.pop_if_empty();
-            segments.push("program");
-            segments.push(program_id);
-            if let Some(edition) = edition {
-                segments.push(&edition.to_string());
-            }
+impl AccountCommand {
+    fn network(&self) -> NetworkName {
+        match self {
+            AccountCommand::New(args) => args.network,
+            AccountCommand::Inspect(args) => args.network,
         }
+    }
+}
 
-        eprintln!("ℹ️  Fetching program '{}' from {}", program_id, url);
+#[derive(Args, Debug)]
+struct AccountNewArgs {
+    /// Network the account is generated for
+    #[arg(long, value_enum, default_value_t = NetworkName::compiled())]
+    network: NetworkName,
 
-        let response = self
-            .client
-            .get(url.clone())
-            .header("Accept", "application/json")
-            .send()
-            .map_err(|e| with_context(format!("failed to fetch program '{program_id}'"), e))?;
+    /// Print the account as JSON
+    #[arg(long, default_value_t = false)]
+    json: bool,
+}
 
-        if !response.status().is_success() {
-            return Err(boxed_err(format!(
-                "request to {} failed with status {}",
-                url,
-                response.status()
-            )));
-        }
+#[derive(Args, Debug)]
+struct AccountInspectArgs {
+    #[command(flatten)]
+    key: KeyArgs,
 
-        let body = response
-            .text()
-            .map_err(|e| with_context("failed to read response body", e))?;
-        let trimmed = body.trim();
-        let source = if trimmed.starts_with('"') {
-            serde_json::from_str::<String>(trimmed)
-                .map_err(|e| with_context("failed to decode program body", e))?
-        } else {
-            body
-        };
+    /// Network the account belongs to
+    #[arg(long, value_enum, default_value_t = NetworkName::compiled())]
+    network: NetworkName,
 
-        Program::<CurrentNetwork>::from_str(&source)
-            .map_err(|e| with_context(format!("failed to parse program '{program_id}'"), e))
-    }
+    /// Print the account as JSON
+    #[arg(long, default_value_t = false)]
+    json: bool,
 }
 
-/// Generate an Aleo authorization string for a program execution.
-#[derive(Parser, Debug)]
-#[command(
-    author,
-    version,
-    about = "Generate an Aleo authorization string for the remote prover",
-    long_about = None,
-)]
+// Where the private key is read from; see `read_private_key`.
+#[derive(Args, Debug)]
+#[command(group(
+    ArgGroup::new("key_source")
+        .required(false)
+        .args(["private_key", "private_key_file"]),
+))]
+struct KeyArgs {
+    /// Private key to use. Visible in shell history and process listings; prefer
+    /// --private-key-file, ALEO_PRIVATE_KEY or the interactive prompt
+    #[arg(short = 'k', long = "private-key", value_name = "KEY")]
+    private_key: Option<String>,
+
+    /// File containing the private key
+    #[arg(long, value_name = "FILE")]
+    private_key_file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
 #[command(group(
     ArgGroup::new("program_source")
         .required(true)
-        .args(["program_file", "program_id"]),
+        .args(["program_file", "program_id", "project_dir"]),
 ))]
-struct Args {
+#[command(group(
+    ArgGroup::new("fee")
+        .required(false)
+        .args(["fee_public", "fee_private"]),
+))]
+// Options that produce a ProveRequest rather than a bare authorization.
+#[command(group(
+    ArgGroup::new("prove_request")
+        .required(false)
+        .multiple(true)
+        .args(["prover_url", "fee_public", "fee_private"]),
+))]
+struct AuthorizeArgs {
     /// Path to the compiled Aleo program file (e.g. build/main.aleo)
     #[arg(short = 'f', long = "program-file", value_name = "FILE")]
     program_file: Option<PathBuf>,
 
+    /// Leo/Aleo project directory; loads build/main.aleo and resolves imports from
+    /// imports/ and build/imports/ before falling back to the network
+    #[arg(short = 'd', long = "project-dir", value_name = "DIR")]
+    project_dir: Option<PathBuf>,
+
     /// Program ID of an on-chain deployment (e.g. my_app.aleo)
     #[arg(short = 'p', long = "program-id", value_name = "ID")]
     program_id: Option<String>,
@@ -157,7 +156,20 @@ struct Args {
     #[arg(long, value_name = "EDITION")]
     edition: Option<u16>,
 
-    /// Override the Provable API base URL (default: uses compile-time network)
+    /// Network to authorize for; also selects the default API base
+    #[arg(long, value_enum, default_value_t = NetworkName::compiled())]
+    network: NetworkName,
+
+    /// Pin the edition of an imported program instead of fetching its latest edition
+    /// (repeatable, e.g. --import-edition token.aleo=2)
+    #[arg(
+        long = "import-edition",
+        value_name = "PROGRAM=EDITION",
+        value_parser = parse_import_edition
+    )]
+    import_editions: Vec<(String, u16)>,
+
+    /// Override the Provable API base URL (default: derived from --network)
     #[arg(long, value_name = "URL")]
     api_base: Option<String>,
 
@@ -169,64 +181,274 @@ struct Args {
     #[arg(short = 'i', long = "input", value_name = "VALUE")]
     inputs: Vec<String>,
 
-    /// Private key that will authorize the execution
-    #[arg(short = 'k', long = "private-key", value_name = "KEY")]
-    private_key: String,
+    /// JSON or TOML file with the function inputs, either as an array or as a map keyed by
+    /// input register (r0, r1, ...); values are Aleo literal strings or typed JSON values
+    #[arg(long, value_name = "FILE", conflicts_with = "inputs")]
+    inputs_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    key: KeyArgs,
+
+    /// Execute the authorization locally without a proof and print its outputs, finalize
+    /// arguments and estimated fee to stderr before producing any output
+    #[arg(long, default_value_t = false)]
+    execute_local: bool,
 
     /// Print the derived account address to stderr for verification
     #[arg(long, default_value_t = false)]
     print_account: bool,
+
+    /// Also authorize a public fee with this priority fee (microcredits) and print a
+    /// complete ProveRequest JSON
+    #[arg(long, value_name = "PRIORITY")]
+    fee_public: Option<u64>,
+
+    /// Also authorize a private fee paid from this credits record (plaintext or
+    /// ciphertext) and print a complete ProveRequest JSON
+    #[arg(long, value_name = "RECORD")]
+    fee_private: Option<String>,
+
+    /// Priority fee (microcredits) for --fee-private
+    #[arg(long, value_name = "PRIORITY", default_value_t = 0, requires = "fee_private")]
+    priority_fee: u64,
+
+    /// Base fee (microcredits) to authorize instead of the locally estimated one
+    #[arg(long, value_name = "MICROCREDITS", requires = "fee")]
+    base_fee: Option<u64>,
+
+    /// Submit the request to a running remote prover (e.g. http://localhost:3030) and wait
+    /// for the transaction
+    #[arg(long, value_name = "URL")]
+    prover_url: Option<String>,
+
+    /// Include the signer's view key in the ProveRequest so the prover returns the records
+    /// the transaction creates for the signer, decrypted
+    #[arg(long, default_value_t = false, requires = "prover_url")]
+    decrypt_records: bool,
+
+    /// Ask the prover to broadcast the proven transaction; needs --prover-url or a fee
+    /// option, as a bare authorization carries no broadcast flag
+    #[arg(long, default_value_t = false, requires = "prove_request")]
+    broadcast: bool,
+
+    /// Seconds between job status polls when using --prover-url
+    #[arg(long, value_name = "SECONDS", default_value_t = 2, requires = "prover_url")]
+    poll_interval: u64,
+
+    /// Seconds to wait for the proof when using --prover-url before cancelling the job
+    /// (0 waits indefinitely)
+    #[arg(long, value_name = "SECONDS", default_value_t = 1800, requires = "prover_url")]
+    wait_timeout: u64,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum NetworkName {
+    Testnet,
+    Mainnet,
+    Canary,
+}
+
+impl NetworkName {
+    /// The network the library was compiled for.
+    fn compiled() -> Self {
+        match NETWORK {
+            "mainnet" => NetworkName::Mainnet,
+            _ => NetworkName::Testnet,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            NetworkName::Testnet => "testnet",
+            NetworkName::Mainnet => "mainnet",
+            NetworkName::Canary => "canary",
+        }
+    }
+}
+
+impl AuthorizeArgs {
+    fn api_base(&self) -> String {
+        self.api_base
+            .clone()
+            .unwrap_or_else(|| ProverConfig::api_base_for_network(self.network.as_str()))
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
 
-    let args = Args::parse();
+    match Cli::parse().command {
+        Command::Authorize(args) => match args.network {
+            NetworkName::Testnet => run::<TestnetV0, AleoTestnetV0>(*args),
+            NetworkName::Mainnet => run::<MainnetV0, AleoV0>(*args),
+            NetworkName::Canary => run::<CanaryV0, AleoCanaryV0>(*args),
+        },
+        Command::Account(command) => match command.network() {
+            NetworkName::Testnet => run_account::<TestnetV0>(command),
+            NetworkName::Mainnet => run_account::<MainnetV0>(command),
+            NetworkName::Canary => run_account::<CanaryV0>(command),
+        },
+    }
+}
+
+fn run_account<N: Network>(command: AccountCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        AccountCommand::New(args) => {
+            let private_key = PrivateKey::<N>::new(&mut rand::thread_rng())
+                .map_err(|e| with_context("failed to generate private key", e))?;
+            print_account(&private_key, true, args.json)
+        }
+        AccountCommand::Inspect(mut args) => {
+            let private_key = parse_private_key::<N>(&mut args.key)?;
+            print_account(&private_key, false, args.json)
+        }
+    }
+}
+
+/// Prints the view key and address of `private_key` to stdout, and the private key itself
+/// when `include_private_key` is set.
+fn print_account<N: Network>(
+    private_key: &PrivateKey<N>,
+    include_private_key: bool,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let view_key = ViewKey::<N>::try_from(private_key)
+        .map_err(|e| with_context("failed to derive view key", e))?;
+    let address = Address::<N>::try_from(&view_key)
+        .map_err(|e| with_context("failed to derive address", e))?;
+
+    if json {
+        let mut account = serde_json::json!({
+            "view_key": view_key.to_string(),
+            "address": address.to_string(),
+        });
+        if include_private_key {
+            account["private_key"] = private_key.to_string().into();
+        }
+        println!("{account}");
+    } else {
+        if include_private_key {
+            println!("Private key: {private_key}");
+        }
+        println!("View key:    {view_key}");
+        println!("Address:     {address}");
+    }
+
+    Ok(())
+}
 
-    if args.program_file.is_some() && args.edition.is_some() {
+fn run<N: Network, A: Aleo<Network = N>>(
+    mut args: AuthorizeArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.program_id.is_none() && args.edition.is_some() {
         return Err(boxed_err(
             "--edition is only supported alongside --program-id",
         ));
     }
 
-    let (program, remote_fetcher) = load_program(&args)?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| with_context("failed to start async runtime", e))?;
+    let resolver = build_resolver::<N>(&args)?;
+
+    let (program, edition) = load_program::<N>(&args, &runtime, &resolver)?;
 
-    let function_id = Identifier::<CurrentNetwork>::from_str(&args.function)
+    let function_id = Identifier::<N>::from_str(&args.function)
         .map_err(|e| with_context(format!("failed to parse function '{}'", args.function), e))?;
 
-    let mut process = Process::<CurrentNetwork>::load()
+    let inputs = collect_inputs(&args, &program, &function_id)?;
+
+    let mut process = Process::<N>::load()
         .map_err(|e| with_context("failed to initialize proving process", e))?;
 
-    if let Some(fetcher) = remote_fetcher.as_ref() {
-        let mut visited = HashSet::new();
-        visited.insert(program.id().to_string());
-        load_remote_dependencies(&mut process, fetcher, &program, &mut visited)?;
-    }
+    load_dependencies(&mut process, &runtime, &resolver, &program, &args)?;
 
     process
-        .add_program(&program)
+        .add_program_with_edition(&program, edition)
         .map_err(|e| with_context("failed to add program to process", e))?;
 
-    let private_key = PrivateKey::<CurrentNetwork>::from_str(&args.private_key)
-        .map_err(|e| with_context("failed to parse private key", e))?;
+    let private_key = parse_private_key::<N>(&mut args.key)?;
 
     let mut rng = rand::thread_rng();
     let authorization = process
-        .authorize::<CurrentAleo, _>(
+        .authorize::<A, _>(
             &private_key,
             program.id(),
             function_id,
-            args.inputs.iter().map(String::as_str),
+            inputs.iter().map(String::as_str),
             &mut rng,
         )
         .map_err(|e| with_context("failed to authorize execution", e))?;
 
-    println!("{authorization}");
+    if args.execute_local {
+        preview_execution::<N, A>(
+            &process,
+            &authorization,
+            &private_key,
+            &args.api_base(),
+            &mut rng,
+        )?;
+    }
+
+    let fee_authorization = if args.fee_public.is_some() || args.fee_private.is_some() {
+        let api_base = args.api_base();
+        Some(authorize_fee::<N, A>(
+            &process,
+            &args,
+            &private_key,
+            &authorization,
+            &api_base,
+            &mut rng,
+        )?)
+    } else {
+        None
+    };
+
+    let view_key = if args.decrypt_records {
+        let view_key = ViewKey::<N>::try_from(&private_key)
+            .map_err(|e| with_context("failed to derive view key", e))?;
+        Some(view_key.to_string())
+    } else {
+        None
+    };
+
+    if let Some(prover_url) = &args.prover_url {
+        let request = build_prove_request(
+            &process,
+            &authorization,
+            fee_authorization.as_ref(),
+            args.broadcast,
+            view_key,
+        )?;
+        submit_to_prover(
+            &runtime,
+            prover_url,
+            &request,
+            Duration::from_secs(args.poll_interval),
+            (args.wait_timeout > 0).then(|| Duration::from_secs(args.wait_timeout)),
+        )?;
+    } else if fee_authorization.is_some() {
+        let request = build_prove_request(
+            &process,
+            &authorization,
+            fee_authorization.as_ref(),
+            args.broadcast,
+            view_key,
+        )?;
+        println!(
+            "{}",
+            serde_json::to_string(&request)
+                .map_err(|e| with_context("failed to encode prove request", e))?
+        );
+    } else {
+        println!("{authorization}");
+    }
 
     if args.print_account {
-        let view_key = ViewKey::<CurrentNetwork>::try_from(&private_key)
+        let view_key = ViewKey::<N>::try_from(&private_key)
             .map_err(|e| with_context("failed to derive view key", e))?;
-        let address = Address::<CurrentNetwork>::try_from(&view_key)
+        let address = Address::<N>::try_from(&view_key)
             .map_err(|e| with_context("failed to derive address", e))?;
         eprintln!("Account address: {address}");
     }
@@ -234,11 +456,394 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-fn load_program(
-    args: &Args,
-) -> Result<(Program<CurrentNetwork>, Option<RemoteFetcher>), Box<dyn std::error::Error>> {
+fn parse_private_key<N: Network>(
+    args: &mut KeyArgs,
+) -> Result<PrivateKey<N>, Box<dyn std::error::Error>> {
+    let private_key = read_private_key(args)?;
+    PrivateKey::<N>::from_str(&private_key)
+        .map_err(|e| with_context("failed to parse private key", e))
+}
+
+/// Reads the private key from, in order: --private-key, --private-key-file, the
+/// `ALEO_PRIVATE_KEY` environment variable, or a prompt on stdin. The returned text is wiped
+/// from memory when dropped.
+fn read_private_key(args: &mut KeyArgs) -> Result<Zeroizing<String>, Box<dyn std::error::Error>> {
+    let raw = if let Some(key) = args.private_key.take() {
+        Zeroizing::new(key)
+    } else if let Some(path) = &args.private_key_file {
+        Zeroizing::new(fs::read_to_string(path).map_err(|e| {
+            with_context(format!("failed to read private key file {}", path.display()), e)
+        })?)
+    } else if let Ok(key) = env::var(PRIVATE_KEY_ENV) {
+        Zeroizing::new(key)
+    } else if io::stdin().is_terminal() {
+        Zeroizing::new(
+            rpassword::prompt_password("Private key: ")
+                .map_err(|e| with_context("failed to read private key", e))?,
+        )
+    } else {
+        let mut line = Zeroizing::new(String::new());
+        io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .map_err(|e| with_context("failed to read private key from stdin", e))?;
+        line
+    };
+
+    let key = Zeroizing::new(raw.trim().to_string());
+    if key.is_empty() {
+        return Err(boxed_err("no private key provided"));
+    }
+    Ok(key)
+}
+
+fn build_prove_request<N: Network>(
+    process: &Process<N>,
+    authorization: &Authorization<N>,
+    fee_authorization: Option<&Authorization<N>>,
+    broadcast: bool,
+    view_key: Option<String>,
+) -> Result<ProveRequest, Box<dyn std::error::Error>> {
+    let fee_authorization = fee_authorization
+        .map(|fee| {
+            serde_json::from_str(&fee.to_string())
+                .map_err(|e| with_context("failed to encode fee authorization", e))
+        })
+        .transpose()?;
+
+    Ok(ProveRequest {
+        authorization: serde_json::from_str(&authorization.to_string())
+            .map_err(|e| with_context("failed to encode authorization", e))?,
+        fee_authorization,
+        broadcast: Some(broadcast),
+        view_key,
+        program_editions: Some(program_editions(process)),
+    })
+}
+
+/// The edition of every program in `process`, so the prover can reload a program it has
+/// loaded at another edition than the one the authorization was created against.
+fn program_editions<N: Network>(process: &Process<N>) -> BTreeMap<String, u16> {
+    process
+        .program_ids()
+        .filter_map(|program_id| {
+            let stack = process.get_stack(*program_id).ok()?;
+            Some((program_id.to_string(), *stack.program_edition()))
+        })
+        .collect()
+}
+
+/// Submits the request through the prover's job API and polls until it finishes.
+fn submit_to_prover(
+    runtime: &tokio::runtime::Runtime,
+    prover_url: &str,
+    request: &ProveRequest,
+    poll_interval: Duration,
+    wait_timeout: Option<Duration>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|e| with_context("failed to build HTTP client", e))?;
+    let client = ProverClient::new(prover_url)?
+        .with_http_client(http)
+        .with_poll_interval(poll_interval);
+
+    eprintln!("ℹ️  Submitting proving job to {}", client.base_url());
+    let result = runtime.block_on(async {
+        let job_id = client.submit_job(request).await?;
+        eprintln!("ℹ️  Job {job_id} accepted, waiting for the proof...");
+
+        let mut last_stage = String::new();
+        let wait = client.wait_for_job(&job_id, |status| {
+            let stage = status.stage.as_deref().unwrap_or("queued");
+            if !status.state.is_finished() && stage != last_stage {
+                eprintln!("ℹ️  Job {job_id}: {stage}");
+                last_stage = stage.to_string();
+            }
+        });
+        let Some(wait_timeout) = wait_timeout else {
+            return Ok::<_, Box<dyn std::error::Error>>(wait.await?);
+        };
+        match tokio::time::timeout(wait_timeout, wait).await {
+            Ok(result) => Ok(result?),
+            Err(_) => {
+                if let Err(e) = client.cancel_job(&job_id).await {
+                    eprintln!("⚠️  Failed to cancel job {job_id}: {e}");
+                }
+                Err(boxed_err(format!(
+                    "job {job_id} did not finish within {}s and was cancelled",
+                    wait_timeout.as_secs()
+                )))
+            }
+        }
+    })?;
+
+    println!("Transaction ID: {}", result.transaction_id);
+    if let Some(error) = &result.records_error {
+        eprintln!("⚠️  The prover could not decrypt the records: {error}");
+    }
+    for record in &result.records {
+        println!(
+            "Record ({}/{} output {}): {}",
+            record.program_id, record.function, record.index, record.plaintext
+        );
+    }
+
+    let broadcast = &result.broadcast;
+    if !broadcast.requested {
+        println!("Broadcast: not requested");
+        return Ok(());
+    }
+
+    let outcome = match (broadcast.status, &broadcast.error) {
+        (Some(status), _) => format!("HTTP {status}"),
+        (None, Some(error)) => error.clone(),
+        (None, None) => "no response".to_string(),
+    };
+    if broadcast.success == Some(true) {
+        println!("Broadcast: accepted ({outcome})");
+        Ok(())
+    } else {
+        println!(
+            "Broadcast: failed ({outcome}) {}",
+            broadcast.response.as_deref().unwrap_or_default()
+        );
+        Err(boxed_err("broadcast was rejected"))
+    }
+}
+
+fn authorize_fee<N: Network, A: Aleo<Network = N>>(
+    process: &Process<N>,
+    args: &AuthorizeArgs,
+    private_key: &PrivateKey<N>,
+    authorization: &Authorization<N>,
+    api_base: &str,
+    rng: &mut ThreadRng,
+) -> Result<Authorization<N>, Box<dyn std::error::Error>> {
+    let execution_id = authorization
+        .to_execution_id()
+        .map_err(|e| with_context("failed to compute execution ID", e))?;
+    let base_fee = match args.base_fee {
+        Some(base_fee) => base_fee,
+        None => estimate_base_fee::<N, A>(process, authorization, api_base, rng)?,
+    };
+
+    if let Some(priority_fee) = args.fee_public {
+        eprintln!(
+            "ℹ️  Authorizing public fee: base {base_fee}, priority {priority_fee} microcredits"
+        );
+        return process
+            .authorize_fee_public::<A, _>(
+                private_key,
+                base_fee,
+                priority_fee,
+                execution_id,
+                rng,
+            )
+            .map_err(|e| with_context("failed to authorize public fee", e));
+    }
+
+    let record = args
+        .fee_private
+        .as_deref()
+        .expect("clap ensures a fee option is provided");
+    let record = parse_fee_record(private_key, record)?;
+    eprintln!(
+        "ℹ️  Authorizing private fee: base {base_fee}, priority {} microcredits",
+        args.priority_fee
+    );
+    process
+        .authorize_fee_private::<A, _>(
+            private_key,
+            record,
+            base_fee,
+            args.priority_fee,
+            execution_id,
+            rng,
+        )
+        .map_err(|e| with_context("failed to authorize private fee", e))
+}
+
+/// Estimates the base fee by executing the authorization locally, without a proof.
+fn estimate_base_fee<N: Network, A: Aleo<Network = N>>(
+    process: &Process<N>,
+    authorization: &Authorization<N>,
+    api_base: &str,
+    rng: &mut ThreadRng,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    // `clone` would share the request queue that `execute` drains, leaving the caller's
+    // authorization empty when its fee is then authorized and it is sent to the prover.
+    let (_, trace) = process
+        .execute::<A, _>(authorization.replicate(), rng)
+        .map_err(|e| with_context("failed to execute authorization", e))?;
+    base_fee_for_transitions(process, trace.transitions(), api_base)
+}
+
+/// Computes the base fee of an execution made of `transitions` at the current block height.
+fn base_fee_for_transitions<N: Network>(
+    process: &Process<N>,
+    transitions: &[Transition<N>],
+    api_base: &str,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let query = Query::<N, BlockMemory<N>>::try_from(api_base)
+        .map_err(|e| with_context(format!("invalid query endpoint '{api_base}'"), e))?;
+    let height = query
+        .current_block_height()
+        .map_err(|e| with_context("failed to fetch current block height", e))?;
+    let consensus_version = N::CONSENSUS_VERSION(height)
+        .map_err(|e| with_context("failed to determine consensus version", e))?;
+    let state_root = query
+        .current_state_root()
+        .map_err(|e| with_context("failed to fetch current state root", e))?;
+
+    let execution = Execution::from(transitions.iter().cloned(), state_root, None)
+        .map_err(|e| with_context("failed to assemble execution", e))?;
+
+    let (_, (_, finalize_cost)) = execution_cost(process, &execution, consensus_version)
+        .map_err(|e| with_context("failed to compute execution cost", e))?;
+    let size = execution
+        .size_in_bytes()
+        .map_err(|e| with_context("failed to measure execution size", e))?;
+
+    Ok(base_fee_for_size::<N>(size, transitions.len(), finalize_cost))
+}
+
+/// The base fee of an execution of `size` bytes without its proof, made of `transitions`
+/// transitions, whose finalize logic costs `finalize_cost` microcredits.
+fn base_fee_for_size<N: Network>(size: u64, transitions: usize, finalize_cost: u64) -> u64 {
+    let size_with_proof = size.saturating_add(proof_storage_allowance(transitions));
+    execution_storage_cost::<N>(size_with_proof).saturating_add(finalize_cost)
+}
+
+/// The number of bytes reserved for the proof of an execution with `transitions` transitions.
+fn proof_storage_allowance(transitions: usize) -> u64 {
+    PROOF_FIXED_BYTES.saturating_add(PROOF_BYTES_PER_TRANSITION.saturating_mul(transitions as u64))
+}
+
+/// Executes the authorization locally, without a proof, and prints the call's outputs, the
+/// records it creates for the signer, its finalize arguments and the estimated base fee.
+fn preview_execution<N: Network, A: Aleo<Network = N>>(
+    process: &Process<N>,
+    authorization: &Authorization<N>,
+    private_key: &PrivateKey<N>,
+    api_base: &str,
+    rng: &mut ThreadRng,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let view_key = ViewKey::<N>::try_from(private_key)
+        .map_err(|e| with_context("failed to derive view key", e))?;
+    let (response, trace) = process
+        .execute::<A, _>(authorization.replicate(), rng)
+        .map_err(|e| with_context("local execution failed", e))?;
+
+    eprintln!("ℹ️  Local execution preview (no proof):");
+    for (index, output) in response.outputs().iter().enumerate() {
+        eprintln!("  output {index}: {output}");
+    }
+
+    for transition in trace.transitions() {
+        let locator = format!("{}/{}", transition.program_id(), transition.function_name());
+        for output in transition.outputs() {
+            match output {
+                Output::Record(_, _, Some(record), ..) if record.is_owner(&view_key) => {
+                    let record = record.decrypt(&view_key).map_err(|e| {
+                        with_context(format!("failed to decrypt record from {locator}"), e)
+                    })?;
+                    eprintln!("  record from {locator}: {record}");
+                }
+                Output::Future(_, Some(future)) => {
+                    let arguments = future
+                        .arguments()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    eprintln!(
+                        "  finalize {}/{}: [{arguments}]",
+                        future.program_id(),
+                        future.function_name()
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    match base_fee_for_transitions(process, trace.transitions(), api_base) {
+        Ok(base_fee) => eprintln!(
+            "  estimated base fee: {base_fee} microcredits ({:.6} credits)",
+            base_fee as f64 / 1_000_000.0
+        ),
+        Err(e) => eprintln!("⚠️  Could not estimate the fee: {e}"),
+    }
+
+    Ok(())
+}
+
+/// Parses a credits record given either as plaintext or as a ciphertext owned by the signer.
+fn parse_fee_record<N: Network>(
+    private_key: &PrivateKey<N>,
+    record: &str,
+) -> Result<Record<N, Plaintext<N>>, Box<dyn std::error::Error>> {
+    let record = record.trim();
+    if !record.starts_with("record1") {
+        return Record::<N, Plaintext<N>>::from_str(record)
+            .map_err(|e| with_context("failed to parse fee record", e));
+    }
+
+    let view_key = ViewKey::<N>::try_from(private_key)
+        .map_err(|e| with_context("failed to derive view key", e))?;
+    Record::<N, Ciphertext<N>>::from_str(record)
+        .map_err(|e| with_context("failed to parse fee record ciphertext", e))?
+        .decrypt(&view_key)
+        .map_err(|e| with_context("failed to decrypt fee record", e))
+}
+
+/// Builds the resolver used for the root program and its imports, applying edition pins.
+fn build_resolver<N: Network>(
+    args: &AuthorizeArgs,
+) -> Result<ProgramResolver<N>, Box<dyn std::error::Error>> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .build()
+        .map_err(|e| with_context("failed to build HTTP client", e))?;
+    let rest_source = RestSource::new(client, &args.api_base()).map_err(boxed_err)?;
+
+    let mut resolver = ProgramResolver::new();
+    if let Some(project_dir) = &args.project_dir {
+        let imports_dirs = [
+            project_dir.join("imports"),
+            project_dir.join("build").join("imports"),
+        ];
+        for imports_dir in imports_dirs {
+            if imports_dir.is_dir() {
+                resolver = resolver.with_source(LocalDirSource::new(imports_dir));
+            }
+        }
+    }
+    resolver = resolver.with_source(rest_source);
+    if let (Some(program_id), Some(edition)) = (&args.program_id, args.edition) {
+        resolver = resolver.with_pinned_edition(parse_program_id(program_id)?, edition);
+    }
+    for (program_id, edition) in &args.import_editions {
+        resolver = resolver.with_pinned_edition(parse_program_id(program_id)?, *edition);
+    }
+
+    Ok(resolver)
+}
+
+/// Loads the root program along with the edition it should be registered under.
+fn load_program<N: Network>(
+    args: &AuthorizeArgs,
+    runtime: &tokio::runtime::Runtime,
+    resolver: &ProgramResolver<N>,
+) -> Result<(Program<N>, u16), Box<dyn std::error::Error>> {
     if let Some(path) = &args.program_file {
-        return Ok((load_local_program(path)?, None));
+        return Ok((load_local_program(path)?, 0));
+    }
+    if let Some(project_dir) = &args.project_dir {
+        let path = project_dir.join("build").join("main.aleo");
+        return Ok((load_local_program(&path)?, 0));
     }
 
     let program_id = args
@@ -251,62 +856,349 @@ fn load_program(
         return Err(boxed_err("--program-id must not be empty"));
     }
 
-    ProgramID::<CurrentNetwork>::from_str(program_id)
-        .map_err(|e| with_context(format!("failed to parse program ID '{program_id}'"), e))?;
-
-    let base_url = args
-        .api_base
-        .clone()
-        .unwrap_or_else(|| ProverConfig::network_api_base());
+    let program_id = parse_program_id::<N>(program_id)?;
+    let ResolvedProgram { program, edition } = runtime
+        .block_on(resolver.resolve(&program_id))
+        .map_err(boxed_err)?;
+    eprintln!("ℹ️  Loaded program '{program_id}' (edition {edition})");
 
-    let fetcher = RemoteFetcher::new(&base_url)?;
-
-    let edition = match args.edition {
-        Some(e) => Some(e),
-        None => fetcher.fetch_latest_edition(program_id)?,
-    };
-
-    let program = fetcher.fetch_program(program_id, edition)?;
-    Ok((program, Some(fetcher)))
+    Ok((program, edition))
 }
 
-fn load_local_program(
+fn load_local_program<N: Network>(
     path: &PathBuf,
-) -> Result<Program<CurrentNetwork>, Box<dyn std::error::Error>> {
+) -> Result<Program<N>, Box<dyn std::error::Error>> {
     let source = fs::read_to_string(path)
         .map_err(|e| with_context(format!("failed to read program {}", path.display()), e))?;
 
-    Program::<CurrentNetwork>::from_str(&source)
+    Program::<N>::from_str(&source)
         .map_err(|e| with_context("failed to parse program", e))
 }
 
-fn load_remote_dependencies(
-    process: &mut Process<CurrentNetwork>,
-    fetcher: &RemoteFetcher,
-    parent: &Program<CurrentNetwork>,
-    visited: &mut HashSet<String>,
+/// Resolves the root program's imports and adds them to `process`, dependencies first.
+fn load_dependencies<N: Network>(
+    process: &mut Process<N>,
+    runtime: &tokio::runtime::Runtime,
+    resolver: &ProgramResolver<N>,
+    program: &Program<N>,
+    args: &AuthorizeArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    for (import_id, _) in parent.imports() {
-        let import_str = import_id.to_string();
-        if !visited.insert(import_str.clone()) {
-            continue;
-        }
+    let imports = program.imports().keys().copied();
+    let dependencies = runtime
+        .block_on(resolver.resolve_with_imports(imports, |id| process.contains_program(id)))
+        .map_err(boxed_err)?;
 
-        // Fetch the latest edition for the dependency
-        let edition = fetcher.fetch_latest_edition(&import_str)?;
-        let dependency = fetcher.fetch_program(&import_str, edition)?;
-        load_remote_dependencies(process, fetcher, &dependency, visited)?;
-        process.add_program(&dependency).map_err(|e| {
-            with_context(
-                format!("failed to add dependency '{import_str}' to process"),
-                e,
-            )
-        })?;
+    let loaded: HashSet<String> = dependencies
+        .iter()
+        .map(|dependency| dependency.program.id().to_string())
+        .collect();
+    for (import_id, _) in args
+        .import_editions
+        .iter()
+        .filter(|(id, _)| !loaded.contains(id))
+    {
+        eprintln!("⚠️  --import-edition for '{import_id}' does not match any import");
+    }
+
+    for ResolvedProgram { program, edition } in dependencies {
+        let import_id = program.id().to_string();
+        eprintln!("ℹ️  Loaded dependency '{import_id}' (edition {edition})");
+        process
+            .add_program_with_edition(&program, edition)
+            .map_err(|e| {
+                with_context(
+                    format!("failed to add dependency '{import_id}' (edition {edition})"),
+                    e,
+                )
+            })?;
     }
 
     Ok(())
 }
 
+fn parse_program_id<N: Network>(
+    program_id: &str,
+) -> Result<ProgramID<N>, Box<dyn std::error::Error>> {
+    ProgramID::<N>::from_str(program_id.trim())
+        .map_err(|e| with_context(format!("failed to parse program ID '{program_id}'"), e))
+}
+
+/// Collects the function inputs from `--inputs-file` or `--input` and checks each one against
+/// the function's declared input types.
+fn collect_inputs<N: Network>(
+    args: &AuthorizeArgs,
+    program: &Program<N>,
+    function_id: &Identifier<N>,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let function = program.get_function(function_id).map_err(|e| {
+        with_context(
+            format!("function '{function_id}' is not defined in '{}'", program.id()),
+            e,
+        )
+    })?;
+    let declared: Vec<(String, ValueType<N>)> = function
+        .inputs()
+        .iter()
+        .map(|input| (input.register().to_string(), input.value_type().clone()))
+        .collect();
+
+    let inputs = match &args.inputs_file {
+        Some(path) => read_inputs_file(path, program, &declared)?,
+        None => args.inputs.clone(),
+    };
+
+    if inputs.len() != declared.len() {
+        return Err(boxed_err(format!(
+            "'{}/{function_id}' takes {} input(s), but {} were given",
+            program.id(),
+            declared.len(),
+            inputs.len()
+        )));
+    }
+
+    for (index, (input, (register, value_type))) in inputs.iter().zip(&declared).enumerate() {
+        check_input(program, input, value_type).map_err(|e| {
+            boxed_err(format!("input {index} ({register} as {value_type}) is invalid: {e}"))
+        })?;
+    }
+
+    Ok(inputs)
+}
+
+/// Reads an inputs file and renders each entry as an Aleo literal. Files ending in `.toml`
+/// are parsed as TOML, everything else as JSON.
+fn read_inputs_file<N: Network>(
+    path: &Path,
+    program: &Program<N>,
+    declared: &[(String, ValueType<N>)],
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| with_context(format!("failed to read inputs {}", path.display()), e))?;
+
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let document: serde_json::Value = if is_toml {
+        toml::from_str(&contents)
+            .map_err(|e| with_context(format!("failed to parse {}", path.display()), e))?
+    } else {
+        serde_json::from_str(&contents)
+            .map_err(|e| with_context(format!("failed to parse {}", path.display()), e))?
+    };
+
+    let values = match document {
+        serde_json::Value::Array(values) => values,
+        serde_json::Value::Object(mut entries) => match entries.remove("inputs") {
+            Some(serde_json::Value::Array(values)) if entries.is_empty() => values,
+            Some(value) => {
+                entries.insert("inputs".to_string(), value);
+                named_inputs(entries, declared)?
+            }
+            None => named_inputs(entries, declared)?,
+        },
+        _ => {
+            return Err(boxed_err(format!(
+                "{} must contain an array or a map of inputs",
+                path.display()
+            )))
+        }
+    };
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let value_type = declared.get(index).map(|(_, value_type)| value_type);
+            render_input(program, value, value_type)
+                .map_err(|e| boxed_err(format!("input {index} in {}: {e}", path.display())))
+        })
+        .collect()
+}
+
+/// Orders a map of `register -> value` entries by the function's declared inputs.
+fn named_inputs<N: Network>(
+    mut entries: serde_json::Map<String, serde_json::Value>,
+    declared: &[(String, ValueType<N>)],
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    let values = declared
+        .iter()
+        .map(|(register, _)| {
+            entries
+                .remove(register)
+                .ok_or_else(|| boxed_err(format!("missing input '{register}'")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !entries.is_empty() {
+        let unknown = entries.keys().cloned().collect::<Vec<_>>().join(", ");
+        return Err(boxed_err(format!("unknown input(s): {unknown}")));
+    }
+
+    Ok(values)
+}
+
+/// Renders a JSON value as an Aleo literal. Strings are passed through untouched; numbers,
+/// booleans, objects and arrays are rendered using the declared input type.
+fn render_input<N: Network>(
+    program: &Program<N>,
+    value: &serde_json::Value,
+    value_type: Option<&ValueType<N>>,
+) -> Result<String, String> {
+    if let serde_json::Value::String(literal) = value {
+        return Ok(literal.clone());
+    }
+
+    match value_type {
+        Some(
+            ValueType::Constant(plaintext_type)
+            | ValueType::Public(plaintext_type)
+            | ValueType::Private(plaintext_type),
+        ) => render_plaintext(program, value, plaintext_type),
+        Some(value_type) => Err(format!("'{value_type}' inputs must be given as strings")),
+        None => Err("inputs beyond the declared ones must be given as strings".to_string()),
+    }
+}
+
+fn render_plaintext<N: Network>(
+    program: &Program<N>,
+    value: &serde_json::Value,
+    plaintext_type: &PlaintextType<N>,
+) -> Result<String, String> {
+    match (value, plaintext_type) {
+        (serde_json::Value::String(literal), _) => Ok(literal.clone()),
+        (serde_json::Value::Bool(flag), PlaintextType::Literal(LiteralType::Boolean)) => {
+            Ok(flag.to_string())
+        }
+        (serde_json::Value::Number(number), PlaintextType::Literal(literal_type)) => {
+            Ok(format!("{number}{literal_type}"))
+        }
+        (serde_json::Value::Object(fields), PlaintextType::Struct(struct_name)) => {
+            let struct_type = program.get_struct(struct_name).map_err(|e| e.to_string())?;
+            let members = struct_type.members();
+
+            if let Some(unknown) = fields
+                .keys()
+                .find(|field| !members.keys().any(|member| member.to_string() == **field))
+            {
+                return Err(format!("struct '{struct_name}' has no member '{unknown}'"));
+            }
+
+            let rendered = members
+                .iter()
+                .map(|(member, member_type)| {
+                    let field = fields.get(&member.to_string()).ok_or_else(|| {
+                        format!("missing member '{member}' of struct '{struct_name}'")
+                    })?;
+                    render_plaintext(program, field, member_type)
+                        .map(|rendered| format!("{member}: {rendered}"))
+                        .map_err(|e| format!("{member}: {e}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(format!("{{ {} }}", rendered.join(", ")))
+        }
+        (serde_json::Value::Array(elements), PlaintextType::Array(array_type)) => {
+            let rendered = elements
+                .iter()
+                .enumerate()
+                .map(|(index, element)| {
+                    render_plaintext(program, element, array_type.next_element_type())
+                        .map_err(|e| format!("[{index}]: {e}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(format!("[{}]", rendered.join(", ")))
+        }
+        _ => Err(format!("expected a value of type '{plaintext_type}', found {value}")),
+    }
+}
+
+/// Checks that an input literal parses and matches the declared input type.
+fn check_input<N: Network>(
+    program: &Program<N>,
+    input: &str,
+    value_type: &ValueType<N>,
+) -> Result<(), String> {
+    let value = Value::<N>::from_str(input).map_err(|e| format!("failed to parse '{input}': {e}"))?;
+
+    match (value_type, &value) {
+        (
+            ValueType::Constant(plaintext_type)
+            | ValueType::Public(plaintext_type)
+            | ValueType::Private(plaintext_type),
+            Value::Plaintext(plaintext),
+        ) => check_plaintext(program, plaintext, plaintext_type),
+        (ValueType::Constant(_) | ValueType::Public(_) | ValueType::Private(_), _) => {
+            Err("expected a plaintext value".to_string())
+        }
+        (ValueType::Record(_) | ValueType::ExternalRecord(_), Value::Record(_)) => Ok(()),
+        (ValueType::Record(_) | ValueType::ExternalRecord(_), _) => {
+            Err("expected a record plaintext (decrypt record ciphertexts first)".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_plaintext<N: Network>(
+    program: &Program<N>,
+    plaintext: &Plaintext<N>,
+    plaintext_type: &PlaintextType<N>,
+) -> Result<(), String> {
+    match (plaintext, plaintext_type) {
+        (Plaintext::Literal(literal, _), PlaintextType::Literal(literal_type)) => {
+            if literal.to_type() == *literal_type {
+                Ok(())
+            } else {
+                Err(format!("expected '{literal_type}', found '{}'", literal.to_type()))
+            }
+        }
+        (Plaintext::Struct(fields, _), PlaintextType::Struct(struct_name)) => {
+            let struct_type = program.get_struct(struct_name).map_err(|e| e.to_string())?;
+            let members = struct_type.members();
+            if fields.len() != members.len() {
+                return Err(format!(
+                    "struct '{struct_name}' has {} member(s), found {}",
+                    members.len(),
+                    fields.len()
+                ));
+            }
+
+            for (member, member_type) in members {
+                let field = fields.get(member).ok_or_else(|| {
+                    format!("missing member '{member}' of struct '{struct_name}'")
+                })?;
+                check_plaintext(program, field, member_type)
+                    .map_err(|e| format!("{member}: {e}"))?;
+            }
+            Ok(())
+        }
+        (Plaintext::Array(elements, _), PlaintextType::Array(array_type)) => {
+            let expected = **array_type.length() as usize;
+            if elements.len() != expected {
+                return Err(format!(
+                    "expected {expected} array element(s), found {}",
+                    elements.len()
+                ));
+            }
+
+            elements.iter().enumerate().try_for_each(|(index, element)| {
+                check_plaintext(program, element, array_type.next_element_type())
+                    .map_err(|e| format!("[{index}]: {e}"))
+            })
+        }
+        _ => Err(format!("expected a value of type '{plaintext_type}'")),
+    }
+}
+
+fn parse_import_edition(value: &str) -> Result<(String, u16), String> {
+    let (program_id, edition) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected PROGRAM=EDITION, got '{value}'"))?;
+    let edition = edition
+        .trim()
+        .parse::<u16>()
+        .map_err(|e| format!("invalid edition in '{value}': {e}"))?;
+    Ok((program_id.trim().to_string(), edition))
+}
+
 fn boxed_err(message: impl Into<String>) -> Box<dyn std::error::Error> {
     Box::new(io::Error::other(message.into()))
 }
@@ -317,3 +1209,149 @@ fn with_context<E: std::fmt::Display>(
 ) -> Box<dyn std::error::Error> {
     boxed_err(format!("{}: {error}", message.into()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_fee_charges_small_executions_per_byte() {
+        let allowance = proof_storage_allowance(1);
+        let size = TestnetV0::EXECUTION_STORAGE_PENALTY_THRESHOLD - allowance;
+
+        assert_eq!(base_fee_for_size::<TestnetV0>(size, 1, 0), size + allowance);
+        assert_eq!(base_fee_for_size::<TestnetV0>(size, 1, 250), size + allowance + 250);
+    }
+
+    #[test]
+    fn base_fee_grows_quadratically_past_the_size_threshold() {
+        let size = 2 * TestnetV0::EXECUTION_STORAGE_PENALTY_THRESHOLD;
+        let size_with_proof = size + proof_storage_allowance(2);
+        let expected =
+            size_with_proof * size_with_proof / TestnetV0::EXECUTION_STORAGE_FEE_SCALING_FACTOR;
+
+        let fee = base_fee_for_size::<TestnetV0>(size, 2, 250);
+        assert_eq!(fee, expected + 250);
+        assert!(fee > size_with_proof + 250, "a per-byte charge would under-estimate {fee}");
+    }
+
+    const INPUTS_PROGRAM: &str = r"
+program inputs_test.aleo;
+
+struct point:
+    x as u32;
+    y as u32;
+
+function move_point:
+    input r0 as point.private;
+    input r1 as u64.public;
+    input r2 as boolean.private;
+    add r1 1u64 into r3;
+    output r3 as u64.public;
+";
+
+    fn inputs_program() -> Program<TestnetV0> {
+        Program::from_str(INPUTS_PROGRAM).unwrap()
+    }
+
+    fn authorize_args(extra: &[&str]) -> AuthorizeArgs {
+        let base = ["authorize", "authorize", "--program-file", "main.aleo", "-F", "move_point"];
+        match Cli::try_parse_from(base.iter().chain(extra).copied()).unwrap().command {
+            Command::Authorize(args) => *args,
+            command => panic!("unexpected command {command:?}"),
+        }
+    }
+
+    fn collect(extra: &[&str]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let program = inputs_program();
+        let function_id = Identifier::from_str("move_point").unwrap();
+        collect_inputs(&authorize_args(extra), &program, &function_id)
+    }
+
+    fn collect_from_file(name: &str, contents: &str) -> Result<Vec<String>, String> {
+        let path = env::temp_dir().join(format!("authorize-{}-{name}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        let result = collect(&["--inputs-file", path.to_str().unwrap()]);
+        fs::remove_file(&path).ok();
+        result.map_err(|e| e.to_string())
+    }
+
+    fn expected_inputs() -> Vec<String> {
+        vec!["{ x: 1u32, y: 2u32 }".to_string(), "5u64".to_string(), "true".to_string()]
+    }
+
+    #[test]
+    fn positional_inputs_are_checked_against_the_signature() {
+        let inputs = collect(&["-i", "{ x: 1u32, y: 2u32 }", "-i", "5u64", "-i", "true"]).unwrap();
+        assert_eq!(inputs, expected_inputs());
+
+        let inputs = collect_from_file("array.json", r#"["{ x: 1u32, y: 2u32 }", 5, true]"#);
+        assert_eq!(inputs.unwrap(), expected_inputs());
+    }
+
+    #[test]
+    fn named_inputs_are_ordered_by_register() {
+        let json = r#"{ "r2": true, "r0": { "x": 1, "y": 2 }, "r1": 5 }"#;
+        assert_eq!(collect_from_file("named.json", json).unwrap(), expected_inputs());
+
+        let toml = "r1 = 5\nr2 = true\nr0 = { x = 1, y = 2 }\n";
+        assert_eq!(collect_from_file("named.toml", toml).unwrap(), expected_inputs());
+
+        let error = collect_from_file("missing.json", r#"{ "r0": "{ x: 1u32, y: 2u32 }" }"#);
+        assert!(error.unwrap_err().contains("missing input 'r1'"));
+        let error = collect_from_file("unknown.toml", "r0 = \"a\"\nr1 = 5\nr2 = true\nr3 = 1\n");
+        assert!(error.unwrap_err().contains("unknown input(s): r3"));
+    }
+
+    #[test]
+    fn inputs_not_matching_the_signature_are_rejected() {
+        let error = collect(&["-i", "{ x: 1u32, y: 2u32 }", "-i", "5u32", "-i", "true"]);
+        let error = error.unwrap_err().to_string();
+        assert!(error.contains("input 1 (r1 as u64.public) is invalid"), "{error}");
+        assert!(error.contains("expected 'u64', found 'u32'"), "{error}");
+
+        let error = collect(&["-i", "{ x: 1u32 }", "-i", "5u64", "-i", "true"]).unwrap_err();
+        assert!(error.to_string().contains("struct 'point' has 2 member(s), found 1"));
+
+        let program = inputs_program();
+        let record = ValueType::from_str("credits.aleo/credits.record").unwrap();
+        let error = check_input(&program, "5u64", &record).unwrap_err();
+        assert!(error.contains("expected a record plaintext"), "{error}");
+        let error = check_input(&program, "5", &ValueType::from_str("u64.public").unwrap());
+        assert!(error.unwrap_err().starts_with("failed to parse '5'"));
+    }
+
+    #[test]
+    fn wrong_input_counts_are_rejected() {
+        let error = collect(&["-i", "{ x: 1u32, y: 2u32 }", "-i", "5u64"]).unwrap_err();
+        let expected = "'inputs_test.aleo/move_point' takes 3 input(s), but 2 were given";
+        assert_eq!(error.to_string(), expected);
+
+        let error = collect_from_file("long.json", r#"["{ x: 1u32, y: 2u32 }", 5, true, 1]"#);
+        assert!(error.unwrap_err().contains("inputs beyond the declared ones must be given as"));
+    }
+
+    #[test]
+    fn typed_values_render_as_aleo_literals() {
+        let program = inputs_program();
+        let render = |value: serde_json::Value, plaintext_type: &str| {
+            render_plaintext(&program, &value, &PlaintextType::from_str(plaintext_type).unwrap())
+        };
+
+        assert_eq!(render(serde_json::json!(7), "i8").unwrap(), "7i8");
+        assert_eq!(render(serde_json::json!(false), "boolean").unwrap(), "false");
+        assert_eq!(render(serde_json::json!("1field"), "u8").unwrap(), "1field");
+        assert_eq!(render(serde_json::json!([1, 2]), "[u16; 2u32]").unwrap(), "[1u16, 2u16]");
+        assert_eq!(
+            render(serde_json::json!([{ "y": 2, "x": 1 }]), "[point; 1u32]").unwrap(),
+            "[{ x: 1u32, y: 2u32 }]"
+        );
+
+        let error = render(serde_json::json!({ "x": 1, "z": 2 }), "point").unwrap_err();
+        assert_eq!(error, "struct 'point' has no member 'z'");
+        let error = render(serde_json::json!({ "x": 1 }), "point").unwrap_err();
+        assert_eq!(error, "missing member 'y' of struct 'point'");
+        let error = render(serde_json::json!([true]), "[u8; 1u32]").unwrap_err();
+        assert_eq!(error, "[0]: expected a value of type 'u8', found true");
+    }
+}
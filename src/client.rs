@@ -0,0 +1,242 @@
+//! Typed async client for the prover HTTP API.
+//!
+//! ```no_run
+//! use remote_prover::{ClientError, ProveRequest, ProverClient};
+//!
+//! # async fn example(request: ProveRequest) -> Result<(), ClientError> {
+//! let client = ProverClient::new("http://localhost:3030")?;
+//! let response = client.prove_job(&request, |_| {}).await?;
+//! println!("{}", response.transaction_id);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::model::{JobState, JobStatus, ProveRequest, ProveResponse};
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use std::{fmt, time::Duration};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The prover base URL could not be used.
+    InvalidUrl(String),
+    /// The request did not reach the prover or its response could not be read.
+    Http(reqwest::Error),
+    /// The prover answered with an error status.
+    Api { status: u16, message: String },
+    /// The prover's response did not have the expected shape.
+    Decode(String),
+    /// A proving job finished without a result.
+    Job {
+        job_id: String,
+        state: JobState,
+        message: String,
+    },
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::InvalidUrl(message) => write!(f, "invalid prover URL: {message}"),
+            ClientError::Http(err) => write!(f, "request to prover failed: {err}"),
+            ClientError::Api { status, message } => {
+                write!(f, "prover returned status {status}: {message}")
+            }
+            ClientError::Decode(message) => write!(f, "unexpected prover response: {message}"),
+            ClientError::Job {
+                job_id,
+                state,
+                message,
+            } => write!(f, "job {job_id} ended as {state:?}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Http(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        ClientError::Http(err)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct JobAccepted {
+    job_id: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+/// Client for a running remote prover.
+#[derive(Clone)]
+pub struct ProverClient {
+    http: reqwest::Client,
+    base: Url,
+    poll_interval: Duration,
+}
+
+impl ProverClient {
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        let base = Url::parse(base_url)
+            .map_err(|err| ClientError::InvalidUrl(format!("'{base_url}': {err}")))?;
+        if base.cannot_be_a_base() {
+            return Err(ClientError::InvalidUrl(format!(
+                "'{base_url}' must be absolute"
+            )));
+        }
+
+        Ok(Self {
+            http: reqwest::Client::new(),
+            base,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        })
+    }
+
+    /// Uses `client` for all requests, e.g. to configure timeouts or TLS.
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http = client;
+        self
+    }
+
+    /// How long [`ProverClient::wait_for_job`] sleeps between status polls.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base
+    }
+
+    /// Checks that the prover is up.
+    pub async fn health(&self) -> Result<(), ClientError> {
+        let response = self.http.get(self.endpoint(&[])).send().await?;
+        decode::<serde_json::Value>(response).await.map(|_| ())
+    }
+
+    /// Proves `request` synchronously through `POST /prove`.
+    pub async fn prove(&self, request: &ProveRequest) -> Result<ProveResponse, ClientError> {
+        let response = self
+            .http
+            .post(self.endpoint(&["prove"]))
+            .json(request)
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Submits `request` as a background job and returns its ID.
+    pub async fn submit_job(&self, request: &ProveRequest) -> Result<String, ClientError> {
+        let response = self
+            .http
+            .post(self.endpoint(&["jobs"]))
+            .json(request)
+            .send()
+            .await?;
+        decode::<JobAccepted>(response)
+            .await
+            .map(|accepted| accepted.job_id)
+    }
+
+    pub async fn job_status(&self, job_id: &str) -> Result<JobStatus, ClientError> {
+        let response = self
+            .http
+            .get(self.endpoint(&["jobs", job_id]))
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Asks the prover to cancel a job and returns its status afterwards.
+    pub async fn cancel_job(&self, job_id: &str) -> Result<JobStatus, ClientError> {
+        let response = self
+            .http
+            .delete(self.endpoint(&["jobs", job_id]))
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    /// Polls a job until it finishes. `on_update` is called with every status received,
+    /// including the final one.
+    pub async fn wait_for_job(
+        &self,
+        job_id: &str,
+        mut on_update: impl FnMut(&JobStatus),
+    ) -> Result<ProveResponse, ClientError> {
+        loop {
+            let status = self.job_status(job_id).await?;
+            on_update(&status);
+
+            if !status.state.is_finished() {
+                tokio::time::sleep(self.poll_interval).await;
+                continue;
+            }
+
+            return match (status.state, status.result) {
+                (JobState::Succeeded, Some(result)) => Ok(result),
+                (JobState::Succeeded, None) => Err(ClientError::Decode(format!(
+                    "job {job_id} succeeded without a result"
+                ))),
+                (state, _) => Err(ClientError::Job {
+                    job_id: job_id.to_string(),
+                    state,
+                    message: status
+                        .error
+                        .unwrap_or_else(|| "no error message".to_string()),
+                }),
+            };
+        }
+    }
+
+    /// Submits `request` as a job and waits for its result.
+    pub async fn prove_job(
+        &self,
+        request: &ProveRequest,
+        on_update: impl FnMut(&JobStatus),
+    ) -> Result<ProveResponse, ClientError> {
+        let job_id = self.submit_job(request).await?;
+        self.wait_for_job(&job_id, on_update).await
+    }
+
+    fn endpoint(&self, path: &[&str]) -> Url {
+        let mut url = self.base.clone();
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .expect("checked in ProverClient::new");
+            segments.pop_if_empty();
+            segments.extend(path);
+        }
+        url
+    }
+}
+
+async fn decode<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, ClientError> {
+    let status = response.status();
+    let body = response.bytes().await?;
+
+    if !status.is_success() {
+        let message = serde_json::from_slice::<ErrorBody>(&body)
+            .map(|error| error.message)
+            .unwrap_or_else(|_| String::from_utf8_lossy(&body).into_owned());
+        return Err(ClientError::Api {
+            status: status.as_u16(),
+            message,
+        });
+    }
+
+    serde_json::from_slice(&body).map_err(|err| ClientError::Decode(err.to_string()))
+}
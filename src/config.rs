@@ -1,20 +1,76 @@
-use crate::NETWORK;
+use crate::{upstream::RetryPolicy, NETWORK};
 use reqwest::Client;
-use std::{env, net::SocketAddr};
+use std::{
+    env,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
 pub const API_BASE_URL: &str = "https://api.explorer.provable.com";
 
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_BATCH_SIZE: usize = 32;
+const DEFAULT_MAX_QUEUED_JOBS: usize = 256;
+
 #[derive(Clone)]
 pub struct ProverConfig {
     listen_addr: SocketAddr,
     http_client: Client,
+    query_endpoint: String,
+    max_concurrent_proofs: usize,
+    job_timeout: Option<Duration>,
+    parallel_fee: bool,
+    max_batch_size: usize,
+    max_queued_jobs: usize,
+    worker_executable: Option<PathBuf>,
+    key_cache_dir: Option<PathBuf>,
+    admin_token: Option<String>,
+    max_loaded_programs: Option<usize>,
+    max_loaded_keys: Option<usize>,
+    pinned_programs: Vec<String>,
+    edition_check_interval: Option<Duration>,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    request_timeout: Duration,
+    retry_policy: RetryPolicy,
+    breaker_threshold: u32,
+    breaker_cooldown: Duration,
 }
 
 impl Default for ProverConfig {
     fn default() -> Self {
         Self {
             listen_addr: SocketAddr::from(([0, 0, 0, 0], 3030)),
-            http_client: Client::new(),
+            http_client: build_http_client(
+                DEFAULT_CONNECT_TIMEOUT,
+                DEFAULT_READ_TIMEOUT,
+                DEFAULT_REQUEST_TIMEOUT,
+            ),
+            query_endpoint: Self::network_api_base(),
+            max_concurrent_proofs: 1,
+            job_timeout: None,
+            parallel_fee: true,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            max_queued_jobs: DEFAULT_MAX_QUEUED_JOBS,
+            worker_executable: None,
+            key_cache_dir: None,
+            admin_token: None,
+            max_loaded_programs: None,
+            max_loaded_keys: None,
+            pinned_programs: Vec::new(),
+            edition_check_interval: None,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
+            breaker_threshold: DEFAULT_BREAKER_THRESHOLD,
+            breaker_cooldown: DEFAULT_BREAKER_COOLDOWN,
         }
     }
 }
@@ -33,9 +89,257 @@ impl ProverConfig {
             }
         }
 
+        if let Ok(value) = env::var("MAX_CONCURRENT_PROOFS") {
+            match value.parse::<usize>() {
+                Ok(parsed) if parsed > 0 => config.max_concurrent_proofs = parsed,
+                _ => eprintln!(
+                    "Invalid MAX_CONCURRENT_PROOFS '{}', using default {}",
+                    value, config.max_concurrent_proofs
+                ),
+            }
+        }
+
+        if let Ok(value) = env::var("PROVER_JOB_TIMEOUT_SECS") {
+            match value.parse::<u64>() {
+                Ok(0) => config.job_timeout = None,
+                Ok(secs) => config.job_timeout = Some(Duration::from_secs(secs)),
+                Err(_) => eprintln!(
+                    "Invalid PROVER_JOB_TIMEOUT_SECS '{}', proving jobs have no deadline",
+                    value
+                ),
+            }
+        }
+
+        if let Ok(value) = env::var("PROVER_PARALLEL_FEE") {
+            match value.to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" => config.parallel_fee = true,
+                "0" | "false" | "no" => config.parallel_fee = false,
+                _ => eprintln!(
+                    "Invalid PROVER_PARALLEL_FEE '{}', proving fees in parallel",
+                    value
+                ),
+            }
+        }
+
+        if let Some(max) = env_parse::<usize>("PROVER_MAX_BATCH_SIZE") {
+            config.max_batch_size = max.max(1);
+        }
+
+        if let Some(max) = env_parse::<usize>("PROVER_MAX_QUEUED_JOBS") {
+            config.max_queued_jobs = max.max(1);
+        }
+
+        if let Ok(value) = env::var("PROVER_WORKER_PROCESSES") {
+            match value.to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" => match env::current_exe() {
+                    Ok(executable) => config.worker_executable = Some(executable),
+                    Err(err) => eprintln!(
+                        "Cannot locate the prover executable for worker processes ({err}), \
+                         proving in-process"
+                    ),
+                },
+                "0" | "false" | "no" => config.worker_executable = None,
+                _ => eprintln!(
+                    "Invalid PROVER_WORKER_PROCESSES '{}', proving in-process",
+                    value
+                ),
+            }
+        }
+
+        match env::var("PROVER_KEY_CACHE_DIR") {
+            Ok(value) if value.trim().is_empty() => config.key_cache_dir = None,
+            Ok(value) => config.key_cache_dir = Some(PathBuf::from(value)),
+            Err(_) => {
+                config.key_cache_dir = env::var_os("HOME")
+                    .map(|home| PathBuf::from(home).join(".remote-prover").join("keys"))
+            }
+        }
+
+        config.admin_token = env::var("PROVER_ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.trim().is_empty());
+
+        if let Ok(value) = env::var("PROVER_MAX_PROGRAMS") {
+            match value.parse::<usize>() {
+                Ok(0) => config.max_loaded_programs = None,
+                Ok(max) => config.max_loaded_programs = Some(max),
+                Err(_) => eprintln!(
+                    "Invalid PROVER_MAX_PROGRAMS '{}', loaded programs are not limited",
+                    value
+                ),
+            }
+        }
+
+        if let Ok(value) = env::var("PROVER_MAX_KEYS") {
+            match value.parse::<usize>() {
+                Ok(0) => config.max_loaded_keys = None,
+                Ok(max) => config.max_loaded_keys = Some(max),
+                Err(_) => eprintln!(
+                    "Invalid PROVER_MAX_KEYS '{}', loaded proving keys are not limited",
+                    value
+                ),
+            }
+        }
+
+        if let Ok(value) = env::var("PROVER_PINNED_PROGRAMS") {
+            config.pinned_programs = value
+                .split(',')
+                .map(str::trim)
+                .filter(|program_id| !program_id.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+
+        if let Ok(value) = env::var("PROVER_EDITION_CHECK_SECS") {
+            match value.parse::<u64>() {
+                Ok(0) => config.edition_check_interval = None,
+                Ok(secs) => config.edition_check_interval = Some(Duration::from_secs(secs)),
+                Err(_) => eprintln!(
+                    "Invalid PROVER_EDITION_CHECK_SECS '{}', edition checks stay disabled",
+                    value
+                ),
+            }
+        }
+
+        let connect_timeout = env_secs("PROVER_HTTP_CONNECT_TIMEOUT_SECS")
+            .unwrap_or(config.connect_timeout);
+        let read_timeout =
+            env_secs("PROVER_HTTP_READ_TIMEOUT_SECS").unwrap_or(config.read_timeout);
+        let request_timeout =
+            env_secs("PROVER_HTTP_TIMEOUT_SECS").unwrap_or(config.request_timeout);
+        config = config.with_http_timeouts(connect_timeout, read_timeout, request_timeout);
+        if let Some(retries) = env_parse("PROVER_HTTP_RETRIES") {
+            config.retry_policy.max_retries = retries;
+        }
+        if let Some(threshold) = env_parse::<u32>("PROVER_CIRCUIT_BREAKER_THRESHOLD") {
+            config.breaker_threshold = threshold.max(1);
+        }
+        if let Some(cooldown) = env_secs("PROVER_CIRCUIT_BREAKER_COOLDOWN_SECS") {
+            config.breaker_cooldown = cooldown;
+        }
+
         config
     }
 
+    /// Overrides the endpoint used to query state roots while proving.
+    ///
+    /// Accepts either a REST base URL or a static JSON query payload.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.query_endpoint = endpoint.into();
+        self
+    }
+
+    /// Sets how many proofs may be generated at the same time.
+    pub fn with_max_concurrent_proofs(mut self, max: usize) -> Self {
+        self.max_concurrent_proofs = max.max(1);
+        self
+    }
+
+    /// Sets the deadline for a single proving job; `None` disables it.
+    pub fn with_job_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.job_timeout = timeout;
+        self
+    }
+
+    /// Whether a fee is proven on its own thread alongside the execution when more than one
+    /// core is available, rather than after it.
+    pub fn with_parallel_fee(mut self, parallel: bool) -> Self {
+        self.parallel_fee = parallel;
+        self
+    }
+
+    /// Sets how many requests a single `/prove/batch` call may carry.
+    pub fn with_max_batch_size(mut self, max: usize) -> Self {
+        self.max_batch_size = max.max(1);
+        self
+    }
+
+    /// Sets how many jobs submitted to `POST /jobs` may be queued or running at once.
+    pub fn with_max_queued_jobs(mut self, max: usize) -> Self {
+        self.max_queued_jobs = max.max(1);
+        self
+    }
+
+    /// Proves in child processes spawned from `executable` (run as `<executable> worker`)
+    /// instead of inside the server process.
+    pub fn with_worker_executable(mut self, executable: impl Into<PathBuf>) -> Self {
+        self.worker_executable = Some(executable.into());
+        self
+    }
+
+    /// Persists synthesized proving and verifying keys under `dir` and reloads them on
+    /// first use after a restart; `None` keeps keys in memory only.
+    pub fn with_key_cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.key_cache_dir = dir;
+        self
+    }
+
+    /// Enables the administrative routes (`DELETE /keys`) for requests that send
+    /// `Authorization: Bearer <token>`. `None` disables them.
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token.filter(|token| !token.trim().is_empty());
+        self
+    }
+
+    /// Limits how many programs (besides `credits.aleo`) the shared process keeps loaded;
+    /// the least recently used ones are evicted first. `None` disables the limit.
+    pub fn with_max_loaded_programs(mut self, max: Option<usize>) -> Self {
+        self.max_loaded_programs = max.filter(|max| *max > 0);
+        self
+    }
+
+    /// Limits how many proving keys the programs in the shared process may hold. Keys are
+    /// the bulk of a loaded program's memory, so this bounds memory where a program count
+    /// cannot; the least recently used programs are evicted with their keys. `None` disables
+    /// the limit.
+    pub fn with_max_loaded_keys(mut self, max: Option<usize>) -> Self {
+        self.max_loaded_keys = max.filter(|max| *max > 0);
+        self
+    }
+
+    /// Programs that are never evicted, e.g. `token_registry.aleo`.
+    pub fn with_pinned_programs(mut self, programs: impl IntoIterator<Item = String>) -> Self {
+        self.pinned_programs = programs.into_iter().collect();
+        self
+    }
+
+    /// How often a loaded program's edition is compared with the network's latest edition;
+    /// a newer edition reloads the program and the programs importing it. `None` disables
+    /// the check.
+    pub fn with_edition_check_interval(mut self, interval: Option<Duration>) -> Self {
+        self.edition_check_interval = interval;
+        self
+    }
+
+    /// Sets the connect timeout, the timeout for each read of a response and the overall
+    /// per-request timeout for explorer API and broadcast requests. Replaces any client set
+    /// before.
+    pub fn with_http_timeouts(
+        mut self,
+        connect: Duration,
+        read: Duration,
+        request: Duration,
+    ) -> Self {
+        self.connect_timeout = connect;
+        self.read_timeout = read;
+        self.request_timeout = request;
+        self.http_client = build_http_client(connect, read, request);
+        self
+    }
+
+    /// Sets how program and edition fetches are retried on errors, `429` and `5xx`.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Fails explorer API fetches fast for `cooldown` after `threshold` consecutive failures.
+    pub fn with_circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.breaker_threshold = threshold.max(1);
+        self.breaker_cooldown = cooldown;
+        self
+    }
+
     pub fn listen_addr(&self) -> SocketAddr {
         self.listen_addr
     }
@@ -44,8 +348,89 @@ impl ProverConfig {
         &self.http_client
     }
 
+    pub fn query_endpoint(&self) -> &str {
+        &self.query_endpoint
+    }
+
+    pub fn max_concurrent_proofs(&self) -> usize {
+        self.max_concurrent_proofs
+    }
+
+    pub fn job_timeout(&self) -> Option<Duration> {
+        self.job_timeout
+    }
+
+    pub fn parallel_fee(&self) -> bool {
+        self.parallel_fee
+    }
+
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    pub fn max_queued_jobs(&self) -> usize {
+        self.max_queued_jobs
+    }
+
+    pub fn worker_executable(&self) -> Option<&Path> {
+        self.worker_executable.as_deref()
+    }
+
+    pub fn key_cache_dir(&self) -> Option<&Path> {
+        self.key_cache_dir.as_deref()
+    }
+
+    pub fn admin_token(&self) -> Option<&str> {
+        self.admin_token.as_deref()
+    }
+
+    pub fn max_loaded_programs(&self) -> Option<usize> {
+        self.max_loaded_programs
+    }
+
+    pub fn max_loaded_keys(&self) -> Option<usize> {
+        self.max_loaded_keys
+    }
+
+    pub fn pinned_programs(&self) -> &[String] {
+        &self.pinned_programs
+    }
+
+    pub fn edition_check_interval(&self) -> Option<Duration> {
+        self.edition_check_interval
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    pub fn read_timeout(&self) -> Duration {
+        self.read_timeout
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    pub fn circuit_breaker_threshold(&self) -> u32 {
+        self.breaker_threshold
+    }
+
+    pub fn circuit_breaker_cooldown(&self) -> Duration {
+        self.breaker_cooldown
+    }
+
     pub fn network_api_base() -> String {
-        format!("{}/v2/{}", API_BASE_URL, NETWORK)
+        Self::api_base_for_network(NETWORK)
+    }
+
+    /// API base for a network chosen at runtime (e.g. `testnet`, `mainnet`, `canary`).
+    pub fn api_base_for_network(network: &str) -> String {
+        format!("{}/v2/{}", API_BASE_URL, network)
     }
 
     pub fn broadcast_endpoint() -> String {
@@ -55,3 +440,34 @@ impl ProverConfig {
         )
     }
 }
+
+fn build_http_client(
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    request_timeout: Duration,
+) -> Client {
+    Client::builder()
+        .connect_timeout(connect_timeout)
+        .read_timeout(read_timeout)
+        .timeout(request_timeout)
+        .build()
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to build HTTP client with timeouts ({err}), using defaults");
+            Client::new()
+        })
+}
+
+fn env_parse<T: FromStr>(name: &str) -> Option<T> {
+    let value = env::var(name).ok()?;
+    match value.trim().parse() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            eprintln!("Invalid {} '{}', using the default", name, value);
+            None
+        }
+    }
+}
+
+fn env_secs(name: &str) -> Option<Duration> {
+    env_parse(name).map(Duration::from_secs)
+}
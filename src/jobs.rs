@@ -0,0 +1,113 @@
+use crate::model::{JobState, JobStatus, ProveResponse};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio_util::sync::CancellationToken;
+
+/// How long finished jobs remain queryable before they are pruned.
+const FINISHED_JOB_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+struct JobEntry {
+    state: JobState,
+    stage: Option<&'static str>,
+    result: Option<ProveResponse>,
+    error: Option<String>,
+    cancel: CancellationToken,
+    updated_at: Instant,
+}
+
+/// In-memory registry of asynchronous proving jobs.
+#[derive(Clone, Default)]
+pub(crate) struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<String, JobEntry>>>,
+}
+
+impl JobRegistry {
+    /// Registers a new queued job and returns its ID and cancellation token, or `None` if
+    /// `max_unfinished` jobs are already queued or running.
+    pub(crate) fn create(&self, max_unfinished: usize) -> Option<(String, CancellationToken)> {
+        let mut jobs = self.jobs.lock();
+        jobs.retain(|_, job| {
+            !job.state.is_finished() || job.updated_at.elapsed() < FINISHED_JOB_RETENTION
+        });
+        let unfinished = jobs.values().filter(|job| !job.state.is_finished()).count();
+        if unfinished >= max_unfinished {
+            return None;
+        }
+
+        let id = format!("{:016x}", rand::random::<u64>());
+        let cancel = CancellationToken::new();
+        jobs.insert(
+            id.clone(),
+            JobEntry {
+                state: JobState::Queued,
+                stage: None,
+                result: None,
+                error: None,
+                cancel: cancel.clone(),
+                updated_at: Instant::now(),
+            },
+        );
+
+        Some((id, cancel))
+    }
+
+    pub(crate) fn set_stage(&self, id: &str, stage: &'static str) {
+        if let Some(job) = self.jobs.lock().get_mut(id) {
+            if !job.state.is_finished() {
+                job.state = JobState::Running;
+                job.stage = Some(stage);
+                job.updated_at = Instant::now();
+            }
+        }
+    }
+
+    /// Records the outcome of a job. Jobs that already finished (e.g. were cancelled) keep
+    /// their existing state.
+    pub(crate) fn finish(
+        &self,
+        id: &str,
+        state: JobState,
+        result: Option<ProveResponse>,
+        error: Option<String>,
+    ) {
+        if let Some(job) = self.jobs.lock().get_mut(id) {
+            if !job.state.is_finished() {
+                job.state = state;
+                job.result = result;
+                job.error = error;
+                job.updated_at = Instant::now();
+            }
+        }
+    }
+
+    /// Cancels a job that has not finished yet and returns its snapshot.
+    pub(crate) fn cancel(&self, id: &str) -> Option<JobStatus> {
+        let mut jobs = self.jobs.lock();
+        let job = jobs.get_mut(id)?;
+        if !job.state.is_finished() {
+            job.cancel.cancel();
+            job.state = JobState::Cancelled;
+            job.error = Some("Proving job was cancelled".to_string());
+            job.updated_at = Instant::now();
+        }
+        Some(snapshot(id, job))
+    }
+
+    pub(crate) fn snapshot(&self, id: &str) -> Option<JobStatus> {
+        self.jobs.lock().get(id).map(|job| snapshot(id, job))
+    }
+}
+
+fn snapshot(id: &str, job: &JobEntry) -> JobStatus {
+    JobStatus {
+        job_id: id.to_string(),
+        state: job.state,
+        stage: job.stage.map(str::to_string),
+        result: job.result.clone(),
+        error: job.error.clone(),
+    }
+}
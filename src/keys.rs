@@ -0,0 +1,273 @@
+//! On-disk cache of synthesized proving and verifying keys.
+//!
+//! Keys are stored per program, edition and function under
+//! `<dir>/<network>/<program>/<edition>-<source digest>/<function>.{prover,verifier}`. Each
+//! file starts with the SHA-256 digest of the key bytes that follow it, so truncated or
+//! corrupted files are discarded and the keys are synthesized again.
+
+use crate::{programs::is_credits, CurrentNetwork, NETWORK};
+use sha2::{Digest, Sha256};
+use snarkvm::prelude::{Authorization, FromBytes, Identifier, ProgramID, ToBytes};
+use snarkvm::synthesizer::{
+    snark::{ProvingKey, VerifyingKey},
+    Process,
+};
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tracing::{debug, info, warn};
+
+const DIGEST_LEN: usize = 32;
+const PROVER_EXTENSION: &str = "prover";
+const VERIFIER_EXTENSION: &str = "verifier";
+
+/// A function whose keys may be cached.
+pub(crate) type KeyedCall = (ProgramID<CurrentNetwork>, Identifier<CurrentNetwork>);
+
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Loads cached keys for every call in `calls` whose keys `process` does not hold yet.
+    /// Returns how many functions were loaded; unusable entries are removed and skipped.
+    pub(crate) fn load(&self, process: &Process<CurrentNetwork>, calls: &[KeyedCall]) -> usize {
+        let mut loaded = 0;
+        for (program_id, function_name) in calls {
+            let Some(base) = self.function_path(process, program_id, function_name) else {
+                continue;
+            };
+            match self.load_function(process, program_id, function_name, &base) {
+                Ok(true) => loaded += 1,
+                Ok(false) => {}
+                Err(err) => {
+                    warn!(
+                        "Discarding cached keys for '{}/{}': {}",
+                        program_id, function_name, err
+                    );
+                    remove_key_files(&base);
+                }
+            }
+        }
+        loaded
+    }
+
+    /// Writes the keys `process` holds for `calls` that are not cached yet. Returns how many
+    /// functions were written.
+    pub(crate) fn store(&self, process: &Process<CurrentNetwork>, calls: &[KeyedCall]) -> usize {
+        let mut stored = 0;
+        for (program_id, function_name) in calls {
+            let Some(base) = self.function_path(process, program_id, function_name) else {
+                continue;
+            };
+            if base.with_extension(PROVER_EXTENSION).exists() {
+                continue;
+            }
+            match store_function(process, program_id, function_name, &base) {
+                Ok(()) => stored += 1,
+                Err(err) => warn!(
+                    "Failed to cache keys for '{}/{}': {}",
+                    program_id, function_name, err
+                ),
+            }
+        }
+        stored
+    }
+
+    /// Deletes the cached keys of `program_id`, or of every program when it is `None`.
+    /// Returns the number of functions whose keys were removed.
+    pub fn purge(&self, program_id: Option<&ProgramID<CurrentNetwork>>) -> Result<usize, String> {
+        let root = match program_id {
+            Some(program_id) => self.network_dir().join(program_id.to_string()),
+            None => self.network_dir(),
+        };
+        let removed = count_key_files(&root)?;
+        match fs::remove_dir_all(&root) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(format!("Failed to remove {}: {err}", root.display())),
+        }
+        info!("Purged {} cached key(s) from {}", removed, root.display());
+        Ok(removed)
+    }
+
+    fn network_dir(&self) -> PathBuf {
+        self.dir.join(NETWORK)
+    }
+
+    /// The path of a function's key files, without extension. `credits.aleo` keys ship with
+    /// snarkvm and are not cached.
+    fn function_path(
+        &self,
+        process: &Process<CurrentNetwork>,
+        program_id: &ProgramID<CurrentNetwork>,
+        function_name: &Identifier<CurrentNetwork>,
+    ) -> Option<PathBuf> {
+        if is_credits(program_id) {
+            return None;
+        }
+        let stack = process.get_stack(*program_id).ok()?;
+        let source_digest = hex(&Sha256::digest(stack.program().to_string().as_bytes())[..8]);
+        Some(
+            self.network_dir()
+                .join(program_id.to_string())
+                .join(format!("{}-{source_digest}", *stack.program_edition()))
+                .join(function_name.to_string()),
+        )
+    }
+
+    fn load_function(
+        &self,
+        process: &Process<CurrentNetwork>,
+        program_id: &ProgramID<CurrentNetwork>,
+        function_name: &Identifier<CurrentNetwork>,
+        base: &Path,
+    ) -> Result<bool, String> {
+        let stack = process.get_stack(*program_id).map_err(|err| err.to_string())?;
+        if stack.contains_proving_key(function_name) {
+            return Ok(false);
+        }
+
+        let Some(prover) = read_checked(&base.with_extension(PROVER_EXTENSION))? else {
+            return Ok(false);
+        };
+        let Some(verifier) = read_checked(&base.with_extension(VERIFIER_EXTENSION))? else {
+            return Err("verifying key is missing".to_string());
+        };
+        let proving_key = ProvingKey::<CurrentNetwork>::from_bytes_le(&prover)
+            .map_err(|err| format!("invalid proving key: {err}"))?;
+        let verifying_key = VerifyingKey::<CurrentNetwork>::from_bytes_le(&verifier)
+            .map_err(|err| format!("invalid verifying key: {err}"))?;
+
+        process
+            .insert_proving_key(program_id, function_name, proving_key)
+            .map_err(|err| err.to_string())?;
+        process
+            .insert_verifying_key(program_id, function_name, verifying_key)
+            .map_err(|err| err.to_string())?;
+        debug!("Loaded cached keys for '{}/{}'", program_id, function_name);
+        Ok(true)
+    }
+}
+
+/// The functions an authorization calls, including nested calls.
+pub(crate) fn keyed_calls(authorization: &Authorization<CurrentNetwork>) -> Vec<KeyedCall> {
+    let mut calls: Vec<KeyedCall> = Vec::new();
+    for request in authorization.to_vec_deque() {
+        let call = (*request.program_id(), *request.function_name());
+        if !calls.contains(&call) {
+            calls.push(call);
+        }
+    }
+    calls
+}
+
+fn store_function(
+    process: &Process<CurrentNetwork>,
+    program_id: &ProgramID<CurrentNetwork>,
+    function_name: &Identifier<CurrentNetwork>,
+    base: &Path,
+) -> Result<(), String> {
+    let proving_key = process
+        .get_proving_key(*program_id, *function_name)
+        .map_err(|err| err.to_string())?;
+    let verifying_key = process
+        .get_verifying_key(*program_id, *function_name)
+        .map_err(|err| err.to_string())?;
+    let prover = proving_key.to_bytes_le().map_err(|err| err.to_string())?;
+    let verifier = verifying_key.to_bytes_le().map_err(|err| err.to_string())?;
+
+    if let Some(parent) = base.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    // The verifier is written first so a present prover file implies a complete entry.
+    write_checked(&base.with_extension(VERIFIER_EXTENSION), &verifier)?;
+    write_checked(&base.with_extension(PROVER_EXTENSION), &prover)?;
+    debug!("Cached keys for '{}/{}'", program_id, function_name);
+    Ok(())
+}
+
+/// Reads a key file and verifies its digest. Returns `Ok(None)` if the file does not exist.
+fn read_checked(path: &Path) -> Result<Option<Vec<u8>>, String> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+    if contents.len() < DIGEST_LEN {
+        return Err(format!("{} is truncated", path.display()));
+    }
+
+    let (digest, bytes) = contents.split_at(DIGEST_LEN);
+    if Sha256::digest(bytes).as_slice() != digest {
+        return Err(format!("{} failed its integrity check", path.display()));
+    }
+    Ok(Some(bytes.to_vec()))
+}
+
+/// Writes `bytes` prefixed with their digest, via a temporary file so readers never see a
+/// partial entry.
+fn write_checked(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let mut contents = Vec::with_capacity(DIGEST_LEN + bytes.len());
+    contents.extend_from_slice(&Sha256::digest(bytes));
+    contents.extend_from_slice(bytes);
+
+    static NEXT_TEMPORARY: AtomicU64 = AtomicU64::new(0);
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(
+        ".tmp-{}-{}",
+        std::process::id(),
+        NEXT_TEMPORARY.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temporary = PathBuf::from(temporary);
+    fs::write(&temporary, contents)
+        .and_then(|_| fs::rename(&temporary, path))
+        .map_err(|err| {
+            let _ = fs::remove_file(&temporary);
+            format!("Failed to write {}: {err}", path.display())
+        })
+}
+
+fn remove_key_files(base: &Path) {
+    for extension in [PROVER_EXTENSION, VERIFIER_EXTENSION] {
+        let _ = fs::remove_file(base.with_extension(extension));
+    }
+}
+
+fn count_key_files(dir: &Path) -> Result<usize, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(format!("Failed to read {}: {err}", dir.display())),
+    };
+
+    let mut count = 0;
+    for entry in entries {
+        let path = entry
+            .map_err(|err| format!("Failed to read {}: {err}", dir.display()))?
+            .path();
+        if path.is_dir() {
+            count += count_key_files(&path)?;
+        } else if path.extension().is_some_and(|extension| extension == PROVER_EXTENSION) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -18,13 +18,30 @@ pub type CurrentAleo = snarkvm::circuit::AleoV0;
 #[cfg(feature = "mainnet")]
 pub const NETWORK: &str = "mainnet";
 
+pub mod client;
 pub mod config;
+pub mod keys;
 pub mod model;
+pub mod resolver;
+pub mod service;
+pub mod upstream;
 
+mod jobs;
 mod programs;
 mod proving;
 mod server;
+mod workers;
 
+pub use client::{ClientError, ProverClient};
 pub use config::{ProverConfig, API_BASE_URL};
-pub use model::ProveRequest;
-pub use server::prover_routes;
+pub use keys::KeyCache;
+pub use model::{
+    BroadcastResult, CallMetrics, CircuitState, DecryptedRecord, FeeInfo, JobState, JobStatus,
+    OutputKind, ProveRequest, ProveResponse, ProvingSummary, ProvingTimings, TransitionOutput,
+    UpstreamHealth,
+};
+pub use resolver::{LocalDirSource, ProgramResolver, ProgramSource, ResolvedProgram, RestSource};
+pub use server::{prover_routes, prover_service_routes};
+pub use service::{ProveFailure, ProveStage, ProverService};
+pub use upstream::{CircuitBreaker, RetryPolicy};
+pub use workers::{run_worker, WORKER_ARG};
@@ -1,20 +1,38 @@
 use parking_lot::RwLock;
-use remote_prover::{prover_routes, CurrentNetwork, ProverConfig, NETWORK};
+use remote_prover::{prover_routes, run_worker, CurrentNetwork, ProverConfig, NETWORK, WORKER_ARG};
 use snarkvm::synthesizer::Process;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{error, info};
 
-#[tokio::main]
-async fn main() {
+fn main() {
     dotenvy::dotenv().ok();
 
     let filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info"));
 
+    if std::env::args().nth(1).as_deref() == Some(WORKER_ARG) {
+        // Stdout carries the worker protocol, so logs go to stderr.
+        tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_writer(std::io::stderr)
+            .init();
+
+        if let Err(err) = run_worker() {
+            error!("Proving worker failed: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     tracing_subscriber::fmt()
         .with_env_filter(filter)
         .init();
 
+    serve();
+}
+
+#[tokio::main]
+async fn serve() {
     info!("Aleo Remote Prover starting...");
     info!("Network: {}", NETWORK);
     info!("HOME: {}", std::env::var("HOME").unwrap_or_else(|_| "~/.aleo (default)".to_string()));
@@ -23,6 +41,17 @@ async fn main() {
     let listen_addr = config.listen_addr();
 
     info!("Listen address: {}", listen_addr);
+    if let Some(executable) = config.worker_executable() {
+        info!(
+            "Proving in {} worker process(es) of {}",
+            config.max_concurrent_proofs(),
+            executable.display()
+        );
+    }
+
+    if let Some(dir) = config.key_cache_dir() {
+        info!("Caching proving keys in {}", dir.display());
+    }
 
     let process = Process::<CurrentNetwork>::load().expect("Failed to initialize snarkvm process");
     let process = Arc::new(RwLock::new(process));
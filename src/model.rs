@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 #[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct ProveRequest {
     pub authorization: serde_json::Value,
@@ -5,4 +7,194 @@ pub struct ProveRequest {
     pub fee_authorization: Option<serde_json::Value>,
     #[serde(default)]
     pub broadcast: Option<bool>,
+    /// View key used to decrypt the records the transaction creates for its owner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_key: Option<String>,
+    /// Editions of the programs the authorization was created against, by program ID. A
+    /// program the prover has loaded at another edition is reloaded before proving.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_editions: Option<BTreeMap<String, u16>>,
+}
+
+/// Body of a successful `POST /prove`, also carried as the `result` of a finished job.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ProveResponse {
+    pub status: String,
+    pub network: String,
+    pub transaction_id: String,
+    pub transaction_type: String,
+    pub execution_id: String,
+    pub transaction: serde_json::Value,
+    pub transaction_payload: String,
+    pub summary: ProvingSummary,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<FeeInfo>,
+    pub broadcast: BroadcastResult,
+    /// Records owned by the request's view key, if one was given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub records: Vec<DecryptedRecord>,
+    /// Why the records could not be decrypted; the transaction is returned regardless.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub records_error: Option<String>,
+}
+
+/// What was proven for the main authorization.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ProvingSummary {
+    pub locator: String,
+    pub outputs: Vec<TransitionOutput>,
+    pub transitions: usize,
+    pub call_metrics: Vec<CallMetrics>,
+    pub is_fee: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<ProvingTimings>,
+}
+
+/// One output of a transition in the proven execution.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct TransitionOutput {
+    pub transition_id: String,
+    pub program_id: String,
+    pub function: String,
+    /// Position of the output within its transition.
+    pub index: usize,
+    pub kind: OutputKind,
+    /// The output ID; for records this is the record commitment.
+    pub id: String,
+    /// Plaintext of constant and public outputs, or the future of a `finalize` call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Ciphertext of private outputs and records.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ciphertext: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputKind {
+    Constant,
+    Public,
+    Private,
+    Record,
+    ExternalRecord,
+    Future,
+}
+
+/// A record output decrypted with the view key from the request.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct DecryptedRecord {
+    pub transition_id: String,
+    pub program_id: String,
+    pub function: String,
+    /// Position of the record within its transition's outputs.
+    pub index: usize,
+    pub commitment: String,
+    pub owner: String,
+    /// Record entries by name, each with its visibility suffix (e.g. `5u64.private`).
+    pub data: BTreeMap<String, String>,
+    pub nonce: String,
+    /// The whole record plaintext, ready to be used as a function input.
+    pub plaintext: String,
+}
+
+/// Circuit size of one call in the proven execution.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct CallMetrics {
+    pub program_id: String,
+    pub function: String,
+    pub instructions: usize,
+    pub request_constraints: u64,
+    pub function_constraints: u64,
+    pub response_constraints: u64,
+}
+
+/// Wall-clock time spent proving, in milliseconds.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ProvingTimings {
+    pub execution_ms: u64,
+    pub fee_ms: Option<u64>,
+    pub total_ms: u64,
+    pub fee_in_parallel: bool,
+}
+
+/// The fee transition included in a proven transaction.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct FeeInfo {
+    pub kind: String,
+    pub transition_id: String,
+    pub amount_microcredits: String,
+    pub base_microcredits: String,
+    pub priority_microcredits: String,
+    pub payer: Option<String>,
+    pub global_state_root: String,
+    pub num_finalize_operations: usize,
+}
+
+/// What happened when the prover broadcast the transaction, if it was asked to.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct BroadcastResult {
+    pub requested: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload_preview: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+impl JobState {
+    pub fn is_finished(self) -> bool {
+        !matches!(self, JobState::Queued | JobState::Running)
+    }
+}
+
+/// Body of `GET /jobs/{id}` and `DELETE /jobs/{id}`.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct JobStatus {
+    pub job_id: String,
+    pub state: JobState,
+    #[serde(default)]
+    pub stage: Option<String>,
+    #[serde(default)]
+    pub result: Option<ProveResponse>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// State of the circuit breaker guarding an upstream API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests are sent normally.
+    Closed,
+    /// Requests fail fast without reaching the API.
+    Open,
+    /// The next request is a trial that decides whether the circuit closes again.
+    HalfOpen,
+}
+
+/// Reported for the explorer API by `GET /`.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct UpstreamHealth {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_in_secs: Option<u64>,
 }
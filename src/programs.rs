@@ -1,210 +1,438 @@
-use crate::CurrentNetwork;
-use parking_lot::RwLock;
-use reqwest::Url;
+use crate::{
+    resolver::{ProgramResolver, ResolvedProgram},
+    CurrentNetwork,
+};
+use parking_lot::{Mutex, RwLock};
 use snarkvm::prelude::*;
 use snarkvm::synthesizer::Process;
-use std::{collections::HashSet, str::FromStr, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
 use tracing::info;
 
-struct ProgramWithEdition {
-    program: Program<CurrentNetwork>,
-    edition: u16,
+/// Makes every program referenced by `authorizations` (and their imports) available in
+/// `process`, fetching each missing program at most once, also across concurrent calls.
+pub async fn ensure_programs_available<'a>(
+    process: &Arc<RwLock<Process<CurrentNetwork>>>,
+    resolver: &ProgramResolver<CurrentNetwork>,
+    authorizations: impl IntoIterator<Item = &'a Authorization<CurrentNetwork>>,
+) -> Result<(), String> {
+    resolver
+        .ensure_in_process(process, root_programs(authorizations))
+        .await
 }
 
-pub async fn fetch_latest_edition(
-    client: &reqwest::Client,
-    base: &Url,
-    program_id: &ProgramID<CurrentNetwork>,
-) -> Result<Option<u16>, String> {
-    let url = build_latest_edition_url(base, program_id)?;
-
-    let response = client
-        .get(url.clone())
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .map_err(|err| format!("Failed to fetch latest edition for '{program_id}': {err}"))?;
+/// The programs `authorizations` call directly.
+pub(crate) fn root_programs<'a>(
+    authorizations: impl IntoIterator<Item = &'a Authorization<CurrentNetwork>>,
+) -> Vec<ProgramID<CurrentNetwork>> {
+    let mut roots: Vec<ProgramID<CurrentNetwork>> = Vec::new();
+    for authorization in authorizations {
+        roots.extend(
+            authorization
+                .to_vec_deque()
+                .into_iter()
+                .map(|request| *request.program_id()),
+        );
+        roots.extend(
+            authorization
+                .transitions()
+                .values()
+                .map(|transition| *transition.program_id()),
+        );
+    }
+    roots
+}
 
-    if !response.status().is_success() {
-        if response.status().as_u16() == 404 {
-            return Ok(None);
+/// The loaded programs `authorizations` need, including imports.
+pub(crate) fn referenced_programs<'a>(
+    process: &Process<CurrentNetwork>,
+    authorizations: impl IntoIterator<Item = &'a Authorization<CurrentNetwork>>,
+) -> Vec<ProgramID<CurrentNetwork>> {
+    let mut visited = HashSet::new();
+    let mut stack = root_programs(authorizations);
+    while let Some(program_id) = stack.pop() {
+        if !visited.insert(program_id) {
+            continue;
+        }
+        if let Ok(loaded) = process.get_stack(program_id) {
+            stack.extend(loaded.program().imports().keys().copied());
         }
-        return Err(format!(
-            "Latest edition request for '{program_id}' failed with status {}",
-            response.status()
-        ));
     }
+    visited.into_iter().collect()
+}
 
-    let body = response
-        .text()
-        .await
-        .map_err(|err| format!("Failed to read latest edition for '{program_id}': {err}"))?;
+static CREDITS_PROGRAM_ID: LazyLock<ProgramID<CurrentNetwork>> =
+    LazyLock::new(|| ProgramID::from_str("credits.aleo").expect("valid program ID"));
 
-    let edition: u16 = body
-        .trim()
-        .parse()
-        .map_err(|err| format!("Failed to parse edition number for '{program_id}': {err}"))?;
+/// Whether `program_id` is `credits.aleo`, which every process loads with its keys.
+pub(crate) fn is_credits(program_id: &ProgramID<CurrentNetwork>) -> bool {
+    *program_id == *CREDITS_PROGRAM_ID
+}
 
-    Ok(Some(edition))
+/// Least-recently-used bookkeeping for the programs in the shared process.
+///
+/// snarkvm cannot remove a program from a [`Process`], so eviction rebuilds the process from
+/// the programs that are kept. The budget limits both the number of programs and the number
+/// of proving keys they hold, as the keys take up most of a loaded program's memory.
+/// `credits.aleo`, pinned programs, programs leased by a request in flight and everything
+/// those import are never evicted.
+pub(crate) struct ProgramCache {
+    max_programs: Option<usize>,
+    max_keys: Option<usize>,
+    pinned: HashSet<ProgramID<CurrentNetwork>>,
+    usage: Mutex<Usage>,
 }
 
-fn build_latest_edition_url(
-    base: &Url,
-    program_id: &ProgramID<CurrentNetwork>,
-) -> Result<Url, String> {
-    let mut url = base.clone();
-    {
-        let mut segments = url
-            .path_segments_mut()
-            .map_err(|_| format!("Program API base '{}' must be absolute", base))?;
-        segments.pop_if_empty();
-        segments.push("program");
-        segments.push(&program_id.to_string());
-        segments.push("latest_edition");
+#[derive(Default)]
+struct Usage {
+    clock: u64,
+    last_used: HashMap<ProgramID<CurrentNetwork>, u64>,
+    in_use: HashMap<ProgramID<CurrentNetwork>, usize>,
+}
+
+/// Keeps programs in the process while a request uses them.
+pub(crate) struct ProgramLease<'a> {
+    cache: &'a ProgramCache,
+    programs: Vec<ProgramID<CurrentNetwork>>,
+}
+
+impl Drop for ProgramLease<'_> {
+    fn drop(&mut self) {
+        let mut usage = self.cache.usage.lock();
+        for program_id in &self.programs {
+            if let Some(count) = usage.in_use.get_mut(program_id) {
+                *count -= 1;
+                if *count == 0 {
+                    usage.in_use.remove(program_id);
+                }
+            }
+        }
     }
-    Ok(url)
 }
 
-pub async fn ensure_programs_available(
-    process: &Arc<RwLock<Process<CurrentNetwork>>>,
-    client: &reqwest::Client,
-    base_url: &str,
-    authorization: &Authorization<CurrentNetwork>,
-) -> Result<(), String> {
-    let base = Url::parse(base_url)
-        .map_err(|err| format!("Invalid program API base '{base_url}': {err}"))?;
+impl ProgramCache {
+    pub(crate) fn new(
+        max_programs: Option<usize>,
+        max_keys: Option<usize>,
+        pinned: impl IntoIterator<Item = ProgramID<CurrentNetwork>>,
+    ) -> Self {
+        Self {
+            max_programs,
+            max_keys,
+            pinned: pinned.into_iter().collect(),
+            usage: Mutex::new(Usage::default()),
+        }
+    }
 
-    let mut stack: Vec<(ProgramID<CurrentNetwork>, bool)> = authorization
-        .to_vec_deque()
-        .into_iter()
-        .map(|request| (*request.program_id(), false))
-        .collect();
-    stack.extend(
-        authorization
-            .transitions()
-            .values()
-            .map(|transition| (*transition.program_id(), false)),
-    );
+    /// Marks `programs` as used now and protects them from eviction until the lease is
+    /// dropped.
+    pub(crate) fn lease(&self, programs: Vec<ProgramID<CurrentNetwork>>) -> ProgramLease<'_> {
+        let mut usage = self.usage.lock();
+        usage.clock += 1;
+        let now = usage.clock;
+        for program_id in &programs {
+            usage.last_used.insert(*program_id, now);
+            *usage.in_use.entry(*program_id).or_default() += 1;
+        }
+        drop(usage);
 
-    let credits_program_id = ProgramID::<CurrentNetwork>::from_str("credits.aleo")
-        .map_err(|err| format!("Failed to parse reference program ID: {err}"))?;
+        ProgramLease {
+            cache: self,
+            programs,
+        }
+    }
 
-    let mut scheduled = HashSet::new();
-    let mut pending: Vec<ProgramWithEdition> = Vec::new();
+    /// Whether `process` holds more programs or proving keys than the budget allows.
+    pub(crate) fn over_budget(&self, process: &Process<CurrentNetwork>) -> bool {
+        !self.within_budget(loaded_programs(process).len(), loaded_key_count(process))
+    }
 
-    while let Some((program_id, ready)) = stack.pop() {
-        if program_id == credits_program_id {
-            continue;
+    fn within_budget(&self, programs: usize, keys: usize) -> bool {
+        self.max_programs.is_none_or(|max| programs <= max)
+            && self.max_keys.is_none_or(|max| keys <= max)
+    }
+
+    /// Replaces the contents of `process` with `fresh` plus the programs that survive
+    /// eviction, least recently used programs going first. Returns the evicted programs.
+    pub(crate) fn evict(
+        &self,
+        process: &RwLock<Process<CurrentNetwork>>,
+        mut fresh: Process<CurrentNetwork>,
+    ) -> Result<Vec<ProgramID<CurrentNetwork>>, String> {
+        let mut guard = process.write();
+        if !self.over_budget(&guard) {
+            return Ok(Vec::new());
         }
+        let loaded = loaded_programs(&guard);
 
-        {
-            let guard = process.read();
-            if guard.contains_program(&program_id) {
-                continue;
+        let mut evicted: HashSet<_> = {
+            let usage = self.usage.lock();
+            let mut candidates: Vec<_> = loaded
+                .iter()
+                .filter(|program_id| {
+                    !self.pinned.contains(*program_id) && !usage.in_use.contains_key(*program_id)
+                })
+                .map(|program_id| {
+                    let last_used = usage.last_used.get(program_id).copied().unwrap_or(0);
+                    (last_used, *program_id)
+                })
+                .collect();
+            candidates.sort_by_key(|(last_used, _)| *last_used);
+
+            let mut programs_left = loaded.len();
+            let mut keys_left = loaded_key_count(&guard);
+            let mut evicted = HashSet::new();
+            for (_, program_id) in candidates {
+                if self.within_budget(programs_left, keys_left) {
+                    break;
+                }
+                programs_left -= 1;
+                keys_left -= key_count(&guard, &program_id);
+                evicted.insert(program_id);
             }
-        }
+            evicted
+        };
 
-        if ready {
-            if let Some(idx) = pending.iter().position(|p| *p.program.id() == program_id) {
-                let ProgramWithEdition { program, edition } = pending.swap_remove(idx);
-                let mut guard = process.write();
-                if !guard.contains_program(program.id()) {
-                    guard
-                        .add_program_with_edition(&program, edition)
-                        .map_err(|err| {
-                            format!(
-                                "Failed to add program '{program_id}' (edition {edition}): {err}"
-                            )
-                        })?;
+        // A kept program needs its imports, so those stay even if they were picked.
+        let mut stack: Vec<_> = loaded
+            .iter()
+            .filter(|program_id| !evicted.contains(*program_id))
+            .copied()
+            .collect();
+        while let Some(program_id) = stack.pop() {
+            let kept = guard
+                .get_stack(program_id)
+                .map_err(|err| format!("Program '{program_id}' is not loaded: {err}"))?;
+            for import_id in kept.program().imports().keys() {
+                if evicted.remove(import_id) {
+                    stack.push(*import_id);
                 }
             }
-            continue;
         }
-
-        if !scheduled.insert(program_id) {
-            continue;
+        if evicted.is_empty() {
+            return Ok(Vec::new());
         }
 
-        let (program, edition) =
-            fetch_remote_program_with_edition(client, &base, &program_id).await?;
-        let imports: Vec<_> = program.imports().keys().copied().collect();
+        let mut added = HashSet::new();
+        for program_id in loaded.iter().filter(|program_id| !evicted.contains(*program_id)) {
+            copy_with_imports(
+                &guard,
+                &mut fresh,
+                *program_id,
+                &HashMap::new(),
+                &HashSet::new(),
+                &mut added,
+            )?;
+        }
+        *guard = fresh;
+        drop(guard);
 
-        pending.push(ProgramWithEdition { program, edition });
-        stack.push((program_id, true));
-        for import_id in imports {
-            stack.push((import_id, false));
+        let mut usage = self.usage.lock();
+        for program_id in &evicted {
+            usage.last_used.remove(program_id);
         }
+        info!(
+            "Evicted {} program(s) from the shared process, {} remain loaded",
+            evicted.len(),
+            loaded.len() - evicted.len()
+        );
+        Ok(evicted.into_iter().collect())
     }
+}
 
-    Ok(())
+/// Rate-limits how often each program's edition is compared with its sources.
+pub(crate) struct EditionChecks {
+    interval: Option<Duration>,
+    checked: Mutex<HashMap<ProgramID<CurrentNetwork>, Instant>>,
 }
 
-pub async fn fetch_remote_program_with_edition(
-    client: &reqwest::Client,
-    base: &Url,
-    program_id: &ProgramID<CurrentNetwork>,
-) -> Result<(Program<CurrentNetwork>, u16), String> {
-    let edition = fetch_latest_edition(client, base, program_id)
-        .await?
-        .unwrap_or(0);
-
-    let url = build_program_url(base, program_id, Some(edition))?;
-    info!(
-        "Fetching program '{}' (edition {}) from {}",
-        program_id,
-        edition,
-        url.as_str()
-    );
-
-    let response = client
-        .get(url.clone())
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .map_err(|err| format!("Failed to fetch program '{program_id}': {err}"))?;
+impl EditionChecks {
+    pub(crate) fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            checked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The programs among `programs` whose edition was not checked within the interval.
+    /// Empty when checks are disabled.
+    pub(crate) fn due(
+        &self,
+        programs: Vec<ProgramID<CurrentNetwork>>,
+    ) -> Vec<ProgramID<CurrentNetwork>> {
+        let Some(interval) = self.interval else {
+            return Vec::new();
+        };
 
-    if !response.status().is_success() {
-        return Err(format!(
-            "Program '{program_id}' request failed with status {}",
-            response.status()
-        ));
+        let checked = self.checked.lock();
+        programs
+            .into_iter()
+            .filter(|program_id| !is_credits(program_id))
+            .filter(|program_id| {
+                checked
+                    .get(program_id)
+                    .is_none_or(|last| last.elapsed() >= interval)
+            })
+            .collect()
     }
 
-    let body = response
-        .text()
-        .await
-        .map_err(|err| format!("Failed to read program '{program_id}': {err}"))?;
-    let trimmed = body.trim();
-    let source = if trimmed.starts_with('"') {
-        serde_json::from_str::<String>(trimmed)
-            .map_err(|err| format!("Failed to decode program '{program_id}': {err}"))?
-    } else {
-        body
-    };
-
-    let program = Program::<CurrentNetwork>::from_str(&source)
-        .map_err(|err| format!("Failed to parse program '{program_id}': {err}"))?;
-
-    Ok((program, edition))
-}
-
-fn build_program_url(
-    base: &Url,
-    program_id: &ProgramID<CurrentNetwork>,
-    edition: Option<u16>,
-) -> Result<Url, String> {
-    let mut url = base.clone();
+    /// Records that the edition of `program_id` was compared with its sources just now. A
+    /// failed comparison is not recorded, so the next request retries it.
+    pub(crate) fn mark_checked(&self, program_id: ProgramID<CurrentNetwork>) {
+        self.checked.lock().insert(program_id, Instant::now());
+    }
+}
+
+/// `stale` together with every loaded program that imports one of them, directly or not.
+fn with_dependents(
+    process: &Process<CurrentNetwork>,
+    stale: impl IntoIterator<Item = ProgramID<CurrentNetwork>>,
+) -> HashSet<ProgramID<CurrentNetwork>> {
+    let mut affected: HashSet<_> = stale.into_iter().collect();
+    let loaded = loaded_programs(process);
+    loop {
+        let before = affected.len();
+        for program_id in &loaded {
+            if affected.contains(program_id) {
+                continue;
+            }
+            let imports_affected = process.get_stack(*program_id).is_ok_and(|stack| {
+                stack
+                    .program()
+                    .imports()
+                    .keys()
+                    .any(|import_id| affected.contains(import_id))
+            });
+            if imports_affected {
+                affected.insert(*program_id);
+            }
+        }
+        if affected.len() == before {
+            return affected;
+        }
+    }
+}
+
+/// Rebuilds `process` from `fresh` with `replacements` in place of the loaded programs of the
+/// same ID. Every other loaded program is copied over, after its imports. The programs
+/// importing a replaced one are determined under the write lock, so ones loaded while the
+/// replacements were fetched are re-registered against them too. Only programs unaffected by
+/// the replacements keep their keys. Returns how many programs were reloaded.
+pub(crate) fn replace_programs(
+    process: &RwLock<Process<CurrentNetwork>>,
+    mut fresh: Process<CurrentNetwork>,
+    replacements: Vec<ResolvedProgram<CurrentNetwork>>,
+) -> Result<usize, String> {
+    let replacements: HashMap<_, _> = replacements
+        .into_iter()
+        .map(|resolved| (*resolved.program.id(), resolved))
+        .collect();
+
+    let mut guard = process.write();
+    let affected = with_dependents(&guard, replacements.keys().copied());
+    let mut added = HashSet::new();
+    for program_id in loaded_programs(&guard)
+        .into_iter()
+        .chain(replacements.keys().copied())
     {
-        let mut segments = url
-            .path_segments_mut()
-            .map_err(|_| format!("Program API base '{}' must be absolute", base))?;
-        segments.pop_if_empty();
-        segments.push("program");
-        segments.push(&program_id.to_string());
-        if let Some(edition) = edition {
-            segments.push(&edition.to_string());
+        copy_with_imports(&guard, &mut fresh, program_id, &replacements, &affected, &mut added)?;
+    }
+    *guard = fresh;
+    Ok(affected.len())
+}
+
+/// How many proving keys the programs in `process` other than `credits.aleo` hold.
+pub(crate) fn loaded_key_count(process: &Process<CurrentNetwork>) -> usize {
+    loaded_programs(process)
+        .iter()
+        .map(|program_id| key_count(process, program_id))
+        .sum()
+}
+
+/// How many functions of `program_id` have a proving key in `process`.
+fn key_count(process: &Process<CurrentNetwork>, program_id: &ProgramID<CurrentNetwork>) -> usize {
+    process.get_stack(*program_id).map_or(0, |stack| {
+        stack
+            .program()
+            .functions()
+            .keys()
+            .filter(|function_name| stack.contains_proving_key(function_name))
+            .count()
+    })
+}
+
+/// The programs in `process` other than `credits.aleo`.
+fn loaded_programs(process: &Process<CurrentNetwork>) -> Vec<ProgramID<CurrentNetwork>> {
+    process
+        .program_ids()
+        .filter(|program_id| !is_credits(program_id))
+        .copied()
+        .collect()
+}
+
+/// Adds `program_id` to `target` after its imports: from `replacements` if it is replaced,
+/// otherwise from `source`, keeping its edition and, unless it is in `stale_keys`, the keys
+/// synthesized for its functions so far.
+fn copy_with_imports(
+    source: &Process<CurrentNetwork>,
+    target: &mut Process<CurrentNetwork>,
+    program_id: ProgramID<CurrentNetwork>,
+    replacements: &HashMap<ProgramID<CurrentNetwork>, ResolvedProgram<CurrentNetwork>>,
+    stale_keys: &HashSet<ProgramID<CurrentNetwork>>,
+    added: &mut HashSet<ProgramID<CurrentNetwork>>,
+) -> Result<(), String> {
+    if !added.insert(program_id) || target.contains_program(&program_id) {
+        return Ok(());
+    }
+
+    if let Some(ResolvedProgram { program, edition }) = replacements.get(&program_id) {
+        for import_id in program.imports().keys() {
+            copy_with_imports(source, target, *import_id, replacements, stale_keys, added)?;
         }
+        return target
+            .add_program_with_edition(program, *edition)
+            .map_err(|err| {
+                format!("Failed to add program '{program_id}' (edition {edition}): {err}")
+            });
+    }
+
+    let stack = source
+        .get_stack(program_id)
+        .map_err(|err| format!("Program '{program_id}' is not loaded: {err}"))?;
+    for import_id in stack.program().imports().keys() {
+        copy_with_imports(source, target, *import_id, replacements, stale_keys, added)?;
     }
 
-    Ok(url)
+    let edition = *stack.program_edition();
+    target
+        .add_program_with_edition(stack.program(), edition)
+        .map_err(|err| {
+            format!("Failed to re-add program '{program_id}' (edition {edition}): {err}")
+        })?;
+
+    if stale_keys.contains(&program_id) {
+        return Ok(());
+    }
+    for function_name in stack.program().functions().keys() {
+        if !stack.contains_proving_key(function_name) {
+            continue;
+        }
+        let (Ok(proving_key), Ok(verifying_key)) = (
+            source.get_proving_key(program_id, *function_name),
+            source.get_verifying_key(program_id, *function_name),
+        ) else {
+            continue;
+        };
+        target
+            .insert_proving_key(&program_id, function_name, proving_key)
+            .and_then(|_| target.insert_verifying_key(&program_id, function_name, verifying_key))
+            .map_err(|err| {
+                format!("Failed to carry over the keys of '{program_id}/{function_name}': {err}")
+            })?;
+    }
+    Ok(())
 }
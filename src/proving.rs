@@ -1,38 +1,50 @@
-use crate::{CurrentAleo, CurrentNetwork};
+use crate::{
+    keys::{keyed_calls, KeyCache},
+    model::{
+        CallMetrics, DecryptedRecord, FeeInfo, OutputKind, ProvingSummary, ProvingTimings,
+        TransitionOutput,
+    },
+    CurrentAleo, CurrentNetwork,
+};
 use parking_lot::RwLock;
 use snarkvm::algorithms::snark::varuna::VarunaVersion;
+use snarkvm::ledger::block::Output;
 use snarkvm::ledger::query::QueryTrait;
 use snarkvm::ledger::{query::Query, store::helpers::memory::BlockMemory};
 use snarkvm::prelude::*;
 use snarkvm::synthesizer::Process;
-use std::sync::Arc;
-
-#[derive(Clone, serde::Serialize)]
-pub(crate) struct FeeInfo {
-    pub(crate) kind: &'static str,
-    pub(crate) transition_id: String,
-    pub(crate) amount_microcredits: String,
-    pub(crate) base_microcredits: String,
-    pub(crate) priority_microcredits: String,
-    pub(crate) payer: Option<String>,
-    pub(crate) global_state_root: String,
-    pub(crate) num_finalize_operations: usize,
-}
+use std::{sync::Arc, time::Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
 
 pub(crate) struct ProvingArtifacts {
-    pub(crate) summary: serde_json::Value,
+    pub(crate) summary: ProvingSummary,
     pub(crate) transaction: Transaction<CurrentNetwork>,
     pub(crate) execution_id: String,
     pub(crate) fee_info: Option<FeeInfo>,
 }
 
+/// Proves `authorization` and, if given, `fee_authorization` into a transaction. With
+/// `parallel_fee` set and more than one core available, the fee is proven on its own thread
+/// while the execution is proven.
 pub fn prove_transaction(
     process: Arc<RwLock<Process<CurrentNetwork>>>,
     authorization: Authorization<CurrentNetwork>,
     fee_authorization: Option<Authorization<CurrentNetwork>>,
     rest_endpoint: String,
+    key_cache: Option<&KeyCache>,
+    parallel_fee: bool,
+    cancel: &CancellationToken,
 ) -> Result<ProvingArtifacts, String> {
-    let mut rng = rand::thread_rng();
+    ensure_not_cancelled(cancel)?;
+    let keyed_calls = keyed_calls(&authorization);
+    if let Some(key_cache) = key_cache {
+        let loaded = key_cache.load(&process.read(), &keyed_calls);
+        if loaded > 0 {
+            info!("Loaded cached keys for {} function(s)", loaded);
+        }
+    }
+
     let query =
         Query::<CurrentNetwork, BlockMemory<CurrentNetwork>>::try_from(rest_endpoint.as_str())
             .map_err(|err| format!("Failed to initialize query: {err}"))?;
@@ -52,6 +64,138 @@ pub fn prove_transaction(
             VarunaVersion::V2
         };
 
+    ensure_not_cancelled(cancel)?;
+    let process = &process;
+    let query = &query;
+    let fee_in_parallel = parallel_fee && fee_authorization.is_some() && available_cores() > 1;
+    let started = Instant::now();
+
+    // The fee transition does not depend on the execution proof, so both can be proven at
+    // the same time when there is more than one core to share. Both draw on snarkVM's
+    // global thread pool, so the gain depends on how much of it one proof leaves idle;
+    // `ProvingTimings` reports it per request.
+    let (execution_result, fee_result) = if fee_in_parallel {
+        let fee_cancel = cancel.child_token();
+        let fee_cancel = &fee_cancel;
+        std::thread::scope(|scope| {
+            let fee_handle = fee_authorization.map(|fee_auth| {
+                scope.spawn(move || {
+                    timed(|| {
+                        prove_fee_authorization(
+                            process,
+                            fee_auth,
+                            query,
+                            consensus_version,
+                            varuna_version,
+                            fee_cancel,
+                        )
+                    })
+                })
+            });
+            let execution_result = timed(|| {
+                prove_execution_authorization(
+                    process,
+                    authorization,
+                    query,
+                    consensus_version,
+                    varuna_version,
+                    cancel,
+                )
+            });
+            // The fee is useless without the execution, so it stops at its next checkpoint
+            // instead of holding the request until it is proven.
+            if execution_result.0.is_err() {
+                fee_cancel.cancel();
+            }
+            let fee_result = fee_handle.map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|_| (Err("Fee proving thread panicked".to_string()), 0))
+            });
+            (execution_result, fee_result)
+        })
+    } else {
+        let execution_result = timed(|| {
+            prove_execution_authorization(
+                process,
+                authorization,
+                query,
+                consensus_version,
+                varuna_version,
+                cancel,
+            )
+        });
+        let fee_result = fee_authorization
+            .filter(|_| execution_result.0.is_ok())
+            .map(|fee_auth| {
+                timed(|| {
+                    prove_fee_authorization(
+                        process,
+                        fee_auth,
+                        query,
+                        consensus_version,
+                        varuna_version,
+                        cancel,
+                    )
+                })
+            });
+        (execution_result, fee_result)
+    };
+
+    let (execution_result, execution_ms) = execution_result;
+    let (execution, mut summary) = execution_result?;
+    if let Some(key_cache) = key_cache {
+        let stored = key_cache.store(&process.read(), &keyed_calls);
+        if stored > 0 {
+            info!("Cached newly synthesized keys for {} function(s)", stored);
+        }
+    }
+    let (fee_for_transaction, fee_ms) = match fee_result {
+        Some((fee_result, fee_ms)) => (Some(fee_result?), Some(fee_ms)),
+        None => (None, None),
+    };
+    let total_ms = started.elapsed().as_millis() as u64;
+
+    info!(
+        "Proving finished in {} ms (execution {} ms, fee {:?} ms, fee in parallel: {})",
+        total_ms, execution_ms, fee_ms, fee_in_parallel
+    );
+    summary.timings = Some(ProvingTimings {
+        execution_ms,
+        fee_ms,
+        total_ms,
+        fee_in_parallel,
+    });
+
+    let execution_id = execution
+        .to_execution_id()
+        .map_err(|err| err.to_string())?
+        .to_string();
+
+    let fee_info = fee_for_transaction.as_ref().map(build_fee_info).transpose()?;
+
+    let transaction = Transaction::from_execution(execution, fee_for_transaction)
+        .map_err(|err| err.to_string())?;
+
+    Ok(ProvingArtifacts {
+        summary,
+        transaction,
+        execution_id,
+        fee_info,
+    })
+}
+
+/// Executes and proves the main authorization, returning the execution and its summary.
+fn prove_execution_authorization(
+    process: &RwLock<Process<CurrentNetwork>>,
+    authorization: Authorization<CurrentNetwork>,
+    query: &Query<CurrentNetwork, BlockMemory<CurrentNetwork>>,
+    consensus_version: ConsensusVersion,
+    varuna_version: VarunaVersion,
+    cancel: &CancellationToken,
+) -> Result<(Execution<CurrentNetwork>, ProvingSummary), String> {
+    let mut rng = rand::thread_rng();
+
     let locator = {
         let request = authorization
             .peek_next()
@@ -59,7 +203,7 @@ pub fn prove_transaction(
         Locator::new(*request.program_id(), *request.function_name()).to_string()
     };
 
-    let (response, mut trace) = {
+    let (_, mut trace) = {
         let guard = process.read();
         authorization
             .check_valid_edition(&guard, consensus_version)
@@ -72,89 +216,192 @@ pub fn prove_transaction(
             .map_err(|err| err.to_string())?
     };
 
-    trace.prepare(&query).map_err(|err| err.to_string())?;
+    ensure_not_cancelled(cancel)?;
+    trace.prepare(query).map_err(|err| err.to_string())?;
+    ensure_not_cancelled(cancel)?;
     let execution = trace
         .prove_execution::<CurrentAleo, _>(&locator, varuna_version, &mut rng)
         .map_err(|err| err.to_string())?;
 
-    let execution_id = execution
-        .to_execution_id()
-        .map_err(|err| err.to_string())?
-        .to_string();
-
-    let call_metrics_json = trace
+    let call_metrics = trace
         .call_metrics()
         .iter()
-        .map(|metrics| {
-            serde_json::json!({
-                "program_id": metrics.program_id.to_string(),
-                "function": metrics.function_name.to_string(),
-                "instructions": metrics.num_instructions,
-                "request_constraints": metrics.num_request_constraints,
-                "function_constraints": metrics.num_function_constraints,
-                "response_constraints": metrics.num_response_constraints,
-            })
+        .map(|metrics| CallMetrics {
+            program_id: metrics.program_id.to_string(),
+            function: metrics.function_name.to_string(),
+            instructions: metrics.num_instructions,
+            request_constraints: metrics.num_request_constraints,
+            function_constraints: metrics.num_function_constraints,
+            response_constraints: metrics.num_response_constraints,
         })
-        .collect::<Vec<_>>();
-
-    let summary = serde_json::json!({
-        "locator": locator,
-        "output_ids": response
-            .output_ids()
-            .iter()
-            .map(|output_id| format!("{output_id:?}"))
-            .collect::<Vec<_>>(),
-        "outputs": response
-            .outputs()
-            .iter()
-            .map(|output| format!("{output:?}"))
-            .collect::<Vec<_>>(),
-        "transitions": trace.transitions().len(),
-        "call_metrics": call_metrics_json,
-        "is_fee": trace.is_fee(),
-    });
+        .collect();
 
-    let (fee_for_transaction, fee_info) = if let Some(fee_auth) = fee_authorization {
-        let mut fee_trace = {
-            let guard = process.read();
-            fee_auth
-                .check_valid_edition(&guard, consensus_version)
-                .map_err(|err| err.to_string())?;
-            fee_auth
-                .check_valid_records(consensus_version)
-                .map_err(|err| err.to_string())?;
-            let (_, trace) = guard
-                .execute::<CurrentAleo, _>(fee_auth, &mut rng)
-                .map_err(|err| err.to_string())?;
-            trace
-        };
+    let summary = ProvingSummary {
+        locator,
+        outputs: execution
+            .transitions()
+            .flat_map(transition_outputs)
+            .collect(),
+        transitions: trace.transitions().len(),
+        call_metrics,
+        is_fee: trace.is_fee(),
+        timings: None,
+    };
+
+    Ok((execution, summary))
+}
+
+/// Describes every output of `transition`, revealing plaintext only where it is public.
+fn transition_outputs(transition: &Transition<CurrentNetwork>) -> Vec<TransitionOutput> {
+    transition
+        .outputs()
+        .iter()
+        .enumerate()
+        .map(|(index, output)| {
+            let (kind, id, value, ciphertext) = match output {
+                Output::Constant(id, plaintext) => (
+                    OutputKind::Constant,
+                    id,
+                    plaintext.as_ref().map(ToString::to_string),
+                    None,
+                ),
+                Output::Public(id, plaintext) => (
+                    OutputKind::Public,
+                    id,
+                    plaintext.as_ref().map(ToString::to_string),
+                    None,
+                ),
+                Output::Private(id, ciphertext) => (
+                    OutputKind::Private,
+                    id,
+                    None,
+                    ciphertext.as_ref().map(ToString::to_string),
+                ),
+                Output::Record(commitment, _, record, ..) => (
+                    OutputKind::Record,
+                    commitment,
+                    None,
+                    record.as_ref().map(ToString::to_string),
+                ),
+                Output::ExternalRecord(id) => (OutputKind::ExternalRecord, id, None, None),
+                Output::Future(id, future) => (
+                    OutputKind::Future,
+                    id,
+                    future.as_ref().map(ToString::to_string),
+                    None,
+                ),
+            };
+
+            TransitionOutput {
+                transition_id: transition.id().to_string(),
+                program_id: transition.program_id().to_string(),
+                function: transition.function_name().to_string(),
+                index,
+                kind,
+                id: id.to_string(),
+                value,
+                ciphertext,
+            }
+        })
+        .collect()
+}
+
+/// Decrypts the records in `transaction` that are owned by `view_key`.
+pub(crate) fn decrypt_records(
+    transaction: &Transaction<CurrentNetwork>,
+    view_key: &ViewKey<CurrentNetwork>,
+) -> Result<Vec<DecryptedRecord>, String> {
+    let mut records = Vec::new();
+    for transition in transaction.transitions() {
+        for (index, output) in transition.outputs().iter().enumerate() {
+            let Output::Record(commitment, _, Some(record), ..) = output else {
+                continue;
+            };
+            if !record.is_owner(view_key) {
+                continue;
+            }
+
+            let record = record.decrypt(view_key).map_err(|err| {
+                format!("Failed to decrypt record {commitment}: {err}")
+            })?;
+            records.push(DecryptedRecord {
+                transition_id: transition.id().to_string(),
+                program_id: transition.program_id().to_string(),
+                function: transition.function_name().to_string(),
+                index,
+                commitment: commitment.to_string(),
+                owner: record.owner().to_string(),
+                data: record
+                    .data()
+                    .iter()
+                    .map(|(name, entry)| (name.to_string(), entry.to_string()))
+                    .collect(),
+                nonce: record.nonce().to_string(),
+                plaintext: record.to_string(),
+            });
+        }
+    }
+    Ok(records)
+}
 
-        fee_trace.prepare(&query).map_err(|err| err.to_string())?;
-        let fee = fee_trace
-            .prove_fee::<CurrentAleo, _>(varuna_version, &mut rng)
+/// Executes and proves the fee authorization.
+fn prove_fee_authorization(
+    process: &RwLock<Process<CurrentNetwork>>,
+    fee_authorization: Authorization<CurrentNetwork>,
+    query: &Query<CurrentNetwork, BlockMemory<CurrentNetwork>>,
+    consensus_version: ConsensusVersion,
+    varuna_version: VarunaVersion,
+    cancel: &CancellationToken,
+) -> Result<Fee<CurrentNetwork>, String> {
+    let mut rng = rand::thread_rng();
+
+    let mut fee_trace = {
+        let guard = process.read();
+        fee_authorization
+            .check_valid_edition(&guard, consensus_version)
             .map_err(|err| err.to_string())?;
-        let fee_info = build_fee_info(&fee)?;
-        (Some(fee), Some(fee_info))
-    } else {
-        (None, None)
+        fee_authorization
+            .check_valid_records(consensus_version)
+            .map_err(|err| err.to_string())?;
+        let (_, trace) = guard
+            .execute::<CurrentAleo, _>(fee_authorization, &mut rng)
+            .map_err(|err| err.to_string())?;
+        trace
     };
 
-    let transaction = Transaction::from_execution(execution, fee_for_transaction)
-        .map_err(|err| err.to_string())?;
+    ensure_not_cancelled(cancel)?;
+    fee_trace.prepare(query).map_err(|err| err.to_string())?;
+    ensure_not_cancelled(cancel)?;
+    fee_trace
+        .prove_fee::<CurrentAleo, _>(varuna_version, &mut rng)
+        .map_err(|err| err.to_string())
+}
 
-    Ok(ProvingArtifacts {
-        summary,
-        transaction,
-        execution_id,
-        fee_info,
-    })
+/// Snarkvm cannot interrupt a proof mid-way, so cancellation is observed between stages.
+fn ensure_not_cancelled(cancel: &CancellationToken) -> Result<(), String> {
+    if cancel.is_cancelled() {
+        return Err("Proving job was cancelled".to_string());
+    }
+    Ok(())
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let started = Instant::now();
+    let value = f();
+    (value, started.elapsed().as_millis() as u64)
+}
+
+fn available_cores() -> usize {
+    std::thread::available_parallelism()
+        .map(|cores| cores.get())
+        .unwrap_or(1)
 }
 
 fn build_fee_info(fee: &Fee<CurrentNetwork>) -> Result<FeeInfo, String> {
     let kind = if fee.is_fee_private() {
-        "private"
+        "private".to_string()
     } else if fee.is_fee_public() {
-        "public"
+        "public".to_string()
     } else {
         return Err("Fee transition is neither private nor public".to_string());
     };
@@ -0,0 +1,476 @@
+//! Program resolution shared by the prover server and the `authorize` CLI.
+//!
+//! A [`ProgramResolver`] asks its [`ProgramSource`]s in order for each program, caches what
+//! it finds, and walks imports so programs can be registered dependencies-first.
+
+use crate::upstream::{send_with_retry, CircuitBreaker, RetryPolicy};
+use futures_util::future::BoxFuture;
+use parking_lot::{Mutex, RwLock};
+use reqwest::{StatusCode, Url};
+use snarkvm::prelude::{Network, Program, ProgramID};
+use snarkvm::synthesizer::Process;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
+use tracing::{debug, info};
+
+/// A program together with the edition it must be registered under.
+#[derive(Clone)]
+pub struct ResolvedProgram<N: Network> {
+    pub program: Program<N>,
+    pub edition: u16,
+}
+
+/// A place programs can be loaded from.
+pub trait ProgramSource<N: Network>: Send + Sync {
+    /// Human-readable description used in logs and errors.
+    fn describe(&self) -> String;
+
+    /// Loads `program_id` at `edition`, or at the latest edition the source knows of when
+    /// `edition` is `None`. Returns `Ok(None)` if the source does not have the program.
+    fn load<'a>(
+        &'a self,
+        program_id: &'a ProgramID<N>,
+        edition: Option<u16>,
+    ) -> BoxFuture<'a, Result<Option<ResolvedProgram<N>>, String>>;
+
+    /// The latest edition of `program_id`, if the source tracks editions.
+    fn latest_edition<'a>(
+        &'a self,
+        _program_id: &'a ProgramID<N>,
+    ) -> BoxFuture<'a, Result<Option<u16>, String>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+/// Resolves programs and their imports from an ordered list of sources.
+pub struct ProgramResolver<N: Network> {
+    sources: Vec<Box<dyn ProgramSource<N>>>,
+    pinned: HashMap<ProgramID<N>, u16>,
+    /// Programs resolved but not handed over to a process yet. [`Self::ensure_in_process`]
+    /// drops what it adds, so a long-running server does not keep a second copy of every
+    /// program it has loaded.
+    cache: Mutex<HashMap<ProgramID<N>, ResolvedProgram<N>>>,
+    /// One lock per program being fetched, so concurrent callers share a single download.
+    in_flight: Mutex<HashMap<ProgramID<N>, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl<N: Network> Default for ProgramResolver<N> {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            pinned: HashMap::new(),
+            cache: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<N: Network> ProgramResolver<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a source; sources are consulted in the order they were added.
+    pub fn with_source(mut self, source: impl ProgramSource<N> + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    /// Always resolves `program_id` at `edition` instead of its latest edition.
+    pub fn with_pinned_edition(mut self, program_id: ProgramID<N>, edition: u16) -> Self {
+        self.pinned.insert(program_id, edition);
+        self
+    }
+
+    /// Resolves a single program, consulting the cache first. Concurrent calls for the same
+    /// program wait for the first one instead of fetching it again.
+    pub async fn resolve(&self, program_id: &ProgramID<N>) -> Result<ResolvedProgram<N>, String> {
+        self.resolve_unless(program_id, |_| false)
+            .await
+            .map(|resolved| resolved.expect("nothing is skipped"))
+    }
+
+    /// Like [`Self::resolve`], returning `Ok(None)` if `skip` holds for `program_id` once the
+    /// fetch in flight for it, if any, has finished. [`Self::ensure_in_process`] drops what it
+    /// adds from the cache, so callers that waited on that fetch find the program in the
+    /// process rather than in the cache.
+    async fn resolve_unless(
+        &self,
+        program_id: &ProgramID<N>,
+        skip: impl Fn(&ProgramID<N>) -> bool,
+    ) -> Result<Option<ResolvedProgram<N>>, String> {
+        if let Some(resolved) = self.cache.lock().get(program_id) {
+            return Ok(Some(resolved.clone()));
+        }
+
+        let flight = self
+            .in_flight
+            .lock()
+            .entry(*program_id)
+            .or_default()
+            .clone();
+        let _flight_guard = flight.lock().await;
+        // The cache is checked before `skip`: a program leaves the cache only after it has
+        // been added to the process, so if the fetch waited for succeeded, `skip` sees it.
+        if let Some(resolved) = self.cache.lock().get(program_id) {
+            return Ok(Some(resolved.clone()));
+        }
+        if skip(program_id) {
+            return Ok(None);
+        }
+
+        let result = self.resolve_from_sources(program_id).await;
+        // Callers already waiting on this lock re-check once it is released; later ones find
+        // the program cached or loaded, or start a new fetch if this one failed.
+        self.in_flight.lock().remove(program_id);
+        result.map(Some)
+    }
+
+    async fn resolve_from_sources(
+        &self,
+        program_id: &ProgramID<N>,
+    ) -> Result<ResolvedProgram<N>, String> {
+        let edition = self.pinned.get(program_id).copied();
+        for source in &self.sources {
+            if let Some(resolved) = source.load(program_id, edition).await? {
+                debug!(
+                    "Resolved program '{}' (edition {}) from {}",
+                    program_id,
+                    resolved.edition,
+                    source.describe()
+                );
+                self.cache.lock().insert(*program_id, resolved.clone());
+                return Ok(resolved);
+            }
+        }
+
+        let searched = self
+            .sources
+            .iter()
+            .map(|source| source.describe())
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(format!(
+            "Program '{program_id}' was not found (searched: {searched})"
+        ))
+    }
+
+    /// The edition `program_id` should be used at: its pinned edition, or the latest edition
+    /// reported by the first source that tracks editions. `Ok(None)` if none does.
+    pub async fn latest_edition(&self, program_id: &ProgramID<N>) -> Result<Option<u16>, String> {
+        if let Some(edition) = self.pinned.get(program_id) {
+            return Ok(Some(*edition));
+        }
+        for source in &self.sources {
+            if let Some(edition) = source.latest_edition(program_id).await? {
+                return Ok(Some(edition));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Forgets the cached copy of `program_id` so the next resolution asks the sources again.
+    pub fn invalidate(&self, program_id: &ProgramID<N>) {
+        self.cache.lock().remove(program_id);
+    }
+
+    /// Resolves `roots` and everything they import, dependencies first. Programs for which
+    /// `skip` returns `true` (e.g. ones already loaded) are neither resolved nor traversed.
+    pub async fn resolve_with_imports(
+        &self,
+        roots: impl IntoIterator<Item = ProgramID<N>>,
+        skip: impl Fn(&ProgramID<N>) -> bool,
+    ) -> Result<Vec<ResolvedProgram<N>>, String> {
+        let mut stack: Vec<(ProgramID<N>, bool)> =
+            roots.into_iter().map(|program_id| (program_id, false)).collect();
+        let mut scheduled = HashSet::new();
+        let mut pending = HashMap::new();
+        let mut ordered = Vec::new();
+
+        while let Some((program_id, ready)) = stack.pop() {
+            if ready {
+                if let Some(resolved) = pending.remove(&program_id) {
+                    ordered.push(resolved);
+                }
+                continue;
+            }
+
+            if skip(&program_id) || !scheduled.insert(program_id) {
+                continue;
+            }
+
+            let Some(resolved) = self.resolve_unless(&program_id, &skip).await? else {
+                continue;
+            };
+            stack.push((program_id, true));
+            stack.extend(
+                resolved
+                    .program
+                    .imports()
+                    .keys()
+                    .map(|import_id| (*import_id, false)),
+            );
+            pending.insert(program_id, resolved);
+        }
+
+        Ok(ordered)
+    }
+
+    /// Resolves whatever `roots` need that `process` does not contain yet and adds it. The
+    /// added programs are dropped from the cache, as `process` now holds them.
+    pub async fn ensure_in_process(
+        &self,
+        process: &RwLock<Process<N>>,
+        roots: impl IntoIterator<Item = ProgramID<N>>,
+    ) -> Result<(), String> {
+        let resolved = self
+            .resolve_with_imports(roots, |program_id| {
+                process.read().contains_program(program_id)
+            })
+            .await?;
+
+        // Each program leaves the cache only once the process holds it, see
+        // `Self::resolve_unless`.
+        let mut guard = process.write();
+        for ResolvedProgram { program, edition } in resolved {
+            if !guard.contains_program(program.id()) {
+                guard
+                    .add_program_with_edition(&program, edition)
+                    .map_err(|err| {
+                        format!(
+                            "Failed to add program '{}' (edition {edition}): {err}",
+                            program.id()
+                        )
+                    })?;
+            }
+            self.invalidate(program.id());
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads programs from the explorer REST API (`{base}/program/{id}[/{edition}]`).
+///
+/// Failed requests are retried according to its [`RetryPolicy`], and its [`CircuitBreaker`]
+/// makes requests fail fast while the API is down.
+pub struct RestSource {
+    client: reqwest::Client,
+    base: Url,
+    retry: RetryPolicy,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl RestSource {
+    pub fn new(client: reqwest::Client, base_url: &str) -> Result<Self, String> {
+        let base = Url::parse(base_url)
+            .map_err(|err| format!("Invalid program API base '{base_url}': {err}"))?;
+        if base.cannot_be_a_base() {
+            return Err(format!("Program API base '{base_url}' must be absolute"));
+        }
+        Ok(Self {
+            client,
+            base,
+            retry: RetryPolicy::default(),
+            breaker: Arc::new(CircuitBreaker::default()),
+        })
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Shares `breaker` with other users of the same API, e.g. to report its state.
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    fn program_url(&self, program_id: &str, suffix: Option<&str>) -> Url {
+        let mut url = self.base.clone();
+        {
+            let mut segments = url
+                .path_segments_mut()
+                .expect("checked in RestSource::new");
+            segments.pop_if_empty();
+            segments.push("program");
+            segments.push(program_id);
+            if let Some(suffix) = suffix {
+                segments.push(suffix);
+            }
+        }
+        url
+    }
+
+    async fn get(&self, url: Url, what: &str) -> Result<reqwest::Response, String> {
+        send_with_retry(&self.breaker, &self.retry, what, || {
+            self.client.get(url.clone()).header("Accept", "application/json")
+        })
+        .await
+    }
+
+    async fn fetch_latest_edition(&self, program_id: &str) -> Result<Option<u16>, String> {
+        let url = self.program_url(program_id, Some("latest_edition"));
+
+        let response = self
+            .get(url, &format!("Failed to fetch latest edition for '{program_id}'"))
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!(
+                "Latest edition request for '{program_id}' failed with status {}",
+                response.status()
+            ));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|err| format!("Failed to read latest edition for '{program_id}': {err}"))?;
+
+        let edition = body
+            .trim()
+            .parse()
+            .map_err(|err| format!("Failed to parse edition number for '{program_id}': {err}"))?;
+
+        Ok(Some(edition))
+    }
+
+    async fn fetch_program<N: Network>(
+        &self,
+        program_id: &str,
+        edition: Option<u16>,
+    ) -> Result<Option<Program<N>>, String> {
+        let url = self.program_url(program_id, edition.map(|e| e.to_string()).as_deref());
+        info!("Fetching program '{}' from {}", program_id, url.as_str());
+
+        let response = self
+            .get(url, &format!("Failed to fetch program '{program_id}'"))
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!(
+                "Program '{program_id}' request failed with status {}",
+                response.status()
+            ));
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|err| format!("Failed to read program '{program_id}': {err}"))?;
+
+        parse_program_body(program_id, &body).map(Some)
+    }
+}
+
+impl<N: Network> ProgramSource<N> for RestSource {
+    fn describe(&self) -> String {
+        self.base.to_string()
+    }
+
+    fn load<'a>(
+        &'a self,
+        program_id: &'a ProgramID<N>,
+        edition: Option<u16>,
+    ) -> BoxFuture<'a, Result<Option<ResolvedProgram<N>>, String>> {
+        Box::pin(async move {
+            let program_id = program_id.to_string();
+            let edition = match edition {
+                Some(edition) => Some(edition),
+                None => self.fetch_latest_edition(&program_id).await?,
+            };
+
+            Ok(self
+                .fetch_program(&program_id, edition)
+                .await?
+                .map(|program| ResolvedProgram {
+                    program,
+                    edition: edition.unwrap_or(0),
+                }))
+        })
+    }
+
+    fn latest_edition<'a>(
+        &'a self,
+        program_id: &'a ProgramID<N>,
+    ) -> BoxFuture<'a, Result<Option<u16>, String>> {
+        Box::pin(async move { self.fetch_latest_edition(&program_id.to_string()).await })
+    }
+}
+
+/// Loads programs from `<dir>/<program_id>` files, e.g. `imports/token.aleo`.
+///
+/// Local files carry no edition, so programs are registered at the requested edition or 0.
+pub struct LocalDirSource {
+    dir: PathBuf,
+}
+
+impl LocalDirSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl<N: Network> ProgramSource<N> for LocalDirSource {
+    fn describe(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn load<'a>(
+        &'a self,
+        program_id: &'a ProgramID<N>,
+        edition: Option<u16>,
+    ) -> BoxFuture<'a, Result<Option<ResolvedProgram<N>>, String>> {
+        Box::pin(async move {
+            let path = self.dir.join(program_id.to_string());
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => {
+                    return Err(format!("Failed to read program {}: {err}", path.display()))
+                }
+            };
+
+            let program = Program::<N>::from_str(&source)
+                .map_err(|err| format!("Failed to parse program {}: {err}", path.display()))?;
+            if program.id() != program_id {
+                return Err(format!(
+                    "{} declares program '{}', expected '{program_id}'",
+                    path.display(),
+                    program.id()
+                ));
+            }
+
+            Ok(Some(ResolvedProgram {
+                program,
+                edition: edition.unwrap_or(0),
+            }))
+        })
+    }
+}
+
+/// The explorer returns program sources as a JSON string; plain-text bodies are accepted too.
+fn parse_program_body<N: Network>(program_id: &str, body: &str) -> Result<Program<N>, String> {
+    let trimmed = body.trim();
+    let source = if trimmed.starts_with('"') {
+        serde_json::from_str::<String>(trimmed)
+            .map_err(|err| format!("Failed to decode program '{program_id}': {err}"))?
+    } else {
+        body.to_string()
+    };
+
+    Program::<N>::from_str(&source)
+        .map_err(|err| format!("Failed to parse program '{program_id}': {err}"))
+}
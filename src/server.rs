@@ -1,44 +1,161 @@
 use crate::{
     config::ProverConfig,
-    model::ProveRequest,
-    programs::ensure_programs_available,
-    proving::prove_transaction,
+    jobs::JobRegistry,
+    model::{CircuitState, JobState, ProveRequest},
+    programs::root_programs,
+    service::{ParsedProveRequest, ProveFailure, ProveStage, ProverService},
     CurrentNetwork, NETWORK,
 };
+use futures_util::{SinkExt, StreamExt};
 use parking_lot::RwLock;
-use snarkvm::{prelude::Authorization, synthesizer::Process};
+use snarkvm::{prelude::ProgramID, synthesizer::Process};
 use std::{str::FromStr, sync::Arc};
-use tracing::{debug, error, info, warn};
-use warp::{http::StatusCode, Filter};
+use tokio::sync::{mpsc, Semaphore};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use warp::{
+    http::StatusCode,
+    ws::{Message, WebSocket, Ws},
+    Filter,
+};
+
+/// Requests one WebSocket connection may have in flight; further ones are rejected with `429`.
+const SOCKET_MAX_IN_FLIGHT: usize = 8;
+/// Replies queued for a WebSocket client before reading its requests pauses.
+const SOCKET_OUTBOX: usize = 64;
 
 #[derive(Clone)]
 struct ProverState {
-    process: Arc<RwLock<Process<CurrentNetwork>>>,
-    config: Arc<ProverConfig>,
+    service: Arc<ProverService>,
+    jobs: JobRegistry,
+}
+
+fn failure_status(failure: &ProveFailure) -> StatusCode {
+    match failure {
+        ProveFailure::BadRequest(_) => StatusCode::BAD_REQUEST,
+        ProveFailure::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        ProveFailure::Cancelled => StatusCode::CONFLICT,
+        ProveFailure::TimedOut(_) => StatusCode::GATEWAY_TIMEOUT,
+    }
+}
+
+fn failure_job_state(failure: &ProveFailure) -> JobState {
+    match failure {
+        ProveFailure::BadRequest(_) | ProveFailure::Internal(_) => JobState::Failed,
+        ProveFailure::Cancelled => JobState::Cancelled,
+        ProveFailure::TimedOut(_) => JobState::TimedOut,
+    }
+}
+
+fn failure_reply(failure: ProveFailure) -> warp::reply::WithStatus<warp::reply::Json> {
+    json_reply(
+        failure_status(&failure),
+        serde_json::json!({ "status": "error", "message": failure.message() }),
+    )
+}
+
+/// A `ProveRequest` submitted over the WebSocket, tagged so replies can be correlated.
+#[derive(serde::Deserialize)]
+struct SocketProveRequest {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(flatten)]
+    request: ProveRequest,
+}
+
+/// Query parameters of `DELETE /keys`.
+#[derive(serde::Deserialize)]
+struct PurgeKeysQuery {
+    #[serde(default)]
+    program: Option<String>,
 }
 
 pub fn prover_routes(
     process: Arc<RwLock<Process<CurrentNetwork>>>,
     config: Arc<ProverConfig>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
-    let state = ProverState { process, config };
+    prover_service_routes(Arc::new(ProverService::new(process, config)))
+}
+
+/// The HTTP and WebSocket API over an existing [`ProverService`].
+pub fn prover_service_routes(
+    service: Arc<ProverService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let state = ProverState {
+        service,
+        jobs: JobRegistry::default(),
+    };
 
-    let prove_route = warp::path("prove")
+    let prove_route = warp::path!("prove")
         .and(warp::post())
         .and(warp::body::json())
         .and(with_state(state.clone()))
         .and_then(handle_prove);
 
-    let health_route = warp::path::end().and(warp::get()).map(|| {
-        json_reply(
-            StatusCode::OK,
-            serde_json::json!({
-                "status": "ok",
-            }),
-        )
-    });
+    let prove_batch_route = warp::path!("prove" / "batch")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_prove_batch);
+
+    let submit_job_route = warp::path!("jobs")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_submit_job);
+
+    let job_status_route = warp::path!("jobs" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_job_status);
+
+    let cancel_job_route = warp::path!("jobs" / String)
+        .and(warp::delete())
+        .and(with_state(state.clone()))
+        .and_then(handle_cancel_job);
 
-    health_route.or(prove_route)
+    let prove_ws_route = warp::path!("prove" / "ws")
+        .and(warp::ws())
+        .and(with_state(state.clone()))
+        .map(|ws: Ws, state: ProverState| {
+            ws.on_upgrade(move |socket| handle_prove_socket(socket, state))
+        });
+
+    let purge_keys_route = warp::path!("keys")
+        .and(warp::delete())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::query::<PurgeKeysQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_purge_keys);
+
+    let health_route = warp::path::end()
+        .and(warp::get())
+        .and(with_state(state))
+        .map(|state: ProverState| {
+            let explorer = state.service.explorer_health();
+            // Proving still works while the explorer API is down, as long as the programs
+            // involved are already loaded.
+            let status = match explorer.state {
+                CircuitState::Closed => "ok",
+                CircuitState::Open | CircuitState::HalfOpen => "degraded",
+            };
+            json_reply(
+                StatusCode::OK,
+                serde_json::json!({
+                    "status": status,
+                    "explorer": explorer,
+                }),
+            )
+        });
+
+    health_route
+        .or(prove_ws_route)
+        .or(prove_batch_route)
+        .or(prove_route)
+        .or(submit_job_route)
+        .or(job_status_route)
+        .or(cancel_job_route)
+        .or(purge_keys_route)
 }
 
 fn with_state(
@@ -51,233 +168,387 @@ async fn handle_prove(
     req: ProveRequest,
     state: ProverState,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    info!(
-        "Received proving request. Broadcast requested: {:?}",
-        req.broadcast.unwrap_or(true)
-    );
+    // Warp drops this future when the client disconnects, which cancels the token and
+    // abandons the proving job.
+    match state.service.prove(&req).await {
+        Ok(body) => Ok(json_reply(StatusCode::OK, body)),
+        Err(failure) => Ok(failure_reply(failure)),
+    }
+}
 
-    let authorization = match parse_authorization("authorization", &req.authorization) {
-        Ok(auth) => auth,
-        Err(err) => {
-            warn!("Invalid authorization payload: {}", err);
-            return Ok(bad_request(err));
-        }
+async fn handle_submit_job(
+    req: ProveRequest,
+    state: ProverState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let request = match ParsedProveRequest::parse(&req) {
+        Ok(request) => request,
+        Err(failure) => return Ok(failure_reply(failure)),
     };
-    debug!("Authorization payload parsed successfully.");
 
-    let fee_authorization = match req.fee_authorization.as_ref() {
-        Some(payload) => match parse_authorization("fee_authorization", payload) {
-            Ok(auth) => Some(auth),
-            Err(err) => {
-                warn!("Invalid fee authorization payload: {}", err);
-                return Ok(bad_request(err));
-            }
-        },
-        None => None,
+    let max_queued_jobs = state.service.config().max_queued_jobs();
+    let Some((job_id, cancel)) = state.jobs.create(max_queued_jobs) else {
+        warn!("Rejected proving job: {} jobs are queued or running.", max_queued_jobs);
+        return Ok(json_reply(
+            StatusCode::TOO_MANY_REQUESTS,
+            serde_json::json!({
+                "status": "error",
+                "message": format!(
+                    "{max_queued_jobs} proving jobs are already queued or running, retry later"
+                ),
+            }),
+        ));
     };
-    if fee_authorization.is_some() {
-        debug!("Fee authorization payload parsed successfully.");
-    }
+    info!("Accepted proving job {}.", job_id);
+
+    let task_id = job_id.clone();
+    tokio::spawn(async move {
+        let jobs = state.jobs.clone();
+        let progress = |stage: ProveStage| jobs.set_stage(&task_id, stage.as_str());
+        match state.service.prove_parsed(request, cancel, progress).await {
+            Ok(body) => jobs.finish(&task_id, JobState::Succeeded, Some(body), None),
+            Err(failure) => {
+                warn!(
+                    "Proving job {} ended without a result: {}",
+                    task_id,
+                    failure.message()
+                );
+                jobs.finish(
+                    &task_id,
+                    failure_job_state(&failure),
+                    None,
+                    Some(failure.message()),
+                )
+            }
+        }
+    });
 
-    let client = state.config.http_client();
-    let api_base = ProverConfig::network_api_base();
+    Ok(json_reply(
+        StatusCode::ACCEPTED,
+        serde_json::json!({
+            "status": "accepted",
+            "job_id": job_id,
+        }),
+    ))
+}
 
-    debug!("Ensuring programs are available locally...");
-    if let Err(err) =
-        ensure_programs_available(&state.process, client, &api_base, &authorization).await
-    {
-        error!("Failed to ensure programs available: {}", err);
-        return Ok(error_reply(err));
+async fn handle_job_status(
+    job_id: String,
+    state: ProverState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.jobs.snapshot(&job_id) {
+        Some(snapshot) => Ok(json_reply(StatusCode::OK, snapshot)),
+        None => Ok(not_found(format!("Unknown job '{job_id}'"))),
     }
+}
 
-    if let Some(fee_auth) = &fee_authorization {
-        if let Err(err) =
-            ensure_programs_available(&state.process, client, &api_base, fee_auth).await
-        {
-            error!("Failed to ensure fee programs available: {}", err);
-            return Ok(error_reply(err));
+async fn handle_cancel_job(
+    job_id: String,
+    state: ProverState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.jobs.cancel(&job_id) {
+        Some(snapshot) => {
+            info!("Cancellation requested for proving job {}.", job_id);
+            Ok(json_reply(StatusCode::OK, snapshot))
         }
+        None => Ok(not_found(format!("Unknown job '{job_id}'"))),
     }
+}
 
-    info!("Starting proof generation...");
-
-    let process_for_exec = state.process.clone();
-    let endpoint = ProverConfig::network_api_base();
-    let fee_authorization_for_exec = fee_authorization.clone();
-
-    let proving_join = tokio::task::spawn_blocking(move || {
-        prove_transaction(
-            process_for_exec,
-            authorization,
-            fee_authorization_for_exec,
-            endpoint,
-        )
-    })
-    .await;
-
-    let artifacts = match proving_join {
-        Ok(Ok(artifacts)) => {
-            info!(
-                "Proof generation successful. Execution ID: {}",
-                artifacts.execution_id
-            );
-            artifacts
-        }
-        Ok(Err(err)) => {
-            error!("Proof generation failed w/ logic error: {}", err);
-            return Ok(error_reply(err));
-        }
-        Err(join_error) => {
-            error!("Worker panicked while proving: {}", join_error);
-            return Ok(error_reply(format!("Worker panicked while proving: {join_error}")));
-        }
-    };
-
-    let transaction_id = artifacts.transaction.id().to_string();
-    info!("Transaction ID: {}", transaction_id);
-
-    let transaction_type = if artifacts.transaction.is_deploy() {
-        "deploy"
-    } else if artifacts.transaction.is_fee() {
-        "fee"
-    } else {
-        "execute"
+async fn handle_purge_keys(
+    authorization: Option<String>,
+    query: PurgeKeysQuery,
+    state: ProverState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Administrative routes do not exist unless a token is configured.
+    let Some(admin_token) = state.service.config().admin_token() else {
+        return Ok(not_found("Not found"));
     };
+    if !bearer_matches(authorization.as_deref(), admin_token) {
+        return Ok(json_reply(
+            StatusCode::UNAUTHORIZED,
+            serde_json::json!({ "status": "error", "message": "Invalid or missing admin token" }),
+        ));
+    }
+    if state.service.key_cache().is_none() {
+        return Ok(not_found("The proving-key cache is disabled"));
+    }
 
-    let transaction_string = match serde_json::to_string(&artifacts.transaction) {
-        Ok(value) => value,
-        Err(err) => {
-            error!("Failed to serialize transaction: {}", err);
-            return Ok(error_reply(format!("Failed to serialize transaction: {err}")));
-        }
+    let program_id = match query.program.as_deref().map(ProgramID::<CurrentNetwork>::from_str) {
+        Some(Ok(program_id)) => Some(program_id),
+        Some(Err(err)) => return Ok(bad_request(format!("Invalid program ID: {err}"))),
+        None => None,
     };
 
-    let transaction_value: serde_json::Value = match serde_json::from_str(&transaction_string) {
-        Ok(value) => value,
-        Err(err) => {
-            error!("Failed to parse transaction JSON: {}", err);
-            return Ok(error_reply(format!("Failed to parse transaction JSON: {err}")));
-        }
-    };
-    let transaction_preview = truncate_for_log(&transaction_string, 256);
-
-    let mut response_json = serde_json::json!({
-        "status": "success",
-        "network": NETWORK,
-        "transaction_id": transaction_id,
-        "transaction_type": transaction_type,
-        "execution_id": artifacts.execution_id,
-        "transaction": transaction_value.clone(),
-        "transaction_payload": transaction_string,
-        "summary": artifacts.summary,
-    });
+    match state.service.purge_keys(program_id).await {
+        Ok(removed) => Ok(json_reply(
+            StatusCode::OK,
+            serde_json::json!({
+                "status": "success",
+                "removed": removed,
+            }),
+        )),
+        Err(err) => Ok(json_reply(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            serde_json::json!({ "status": "error", "message": err }),
+        )),
+    }
+}
 
-    if let Some(fee_info) = artifacts.fee_info {
-        if let Some(object) = response_json.as_object_mut() {
-            object.insert(
-                "fee".to_string(),
-                serde_json::to_value(fee_info).unwrap_or(serde_json::Value::Null),
-            );
-        }
+async fn handle_prove_batch(
+    requests: Vec<ProveRequest>,
+    state: ProverState,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if requests.is_empty() {
+        return Ok(bad_request("Batch must contain at least one request"));
+    }
+    let max_batch_size = state.service.config().max_batch_size();
+    if requests.len() > max_batch_size {
+        return Ok(json_reply(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            serde_json::json!({
+                "status": "error",
+                "message": format!(
+                    "Batch has {} requests, at most {max_batch_size} are accepted",
+                    requests.len()
+                ),
+            }),
+        ));
+    }
+    info!("Received batch of {} proving requests.", requests.len());
+
+    let parsed: Vec<_> = requests.iter().map(ParsedProveRequest::parse).collect();
+
+    // Resolve the programs for the whole batch up front so shared dependencies are fetched
+    // once. Items calling a program that failed to load fail with its error instead of
+    // fetching it again.
+    let authorizations = parsed
+        .iter()
+        .flatten()
+        .flat_map(ParsedProveRequest::authorizations);
+    let unavailable = state.service.prefetch_programs(authorizations).await;
+    for (program_id, err) in &unavailable {
+        warn!("Failed to prefetch '{}' for batch: {}", program_id, err);
     }
 
-    let broadcast_requested = req.broadcast.unwrap_or(true);
-    if broadcast_requested {
-        let endpoint = ProverConfig::broadcast_endpoint();
-        let client = state.config.http_client();
-        info!("Broadcasting transaction {} to {}", transaction_id, endpoint);
-
-        let broadcast_meta = match client.post(&endpoint).json(&transaction_value).send().await {
-            Ok(resp) => {
-                let status = resp.status();
-                let body = match resp.text().await {
-                    Ok(text) => truncate_for_log(&text, 256),
-                    Err(err) => {
-                        error!("Error reading broadcast response body: {}", err);
-                        format!("<error reading body: {err}>")
+    let results = futures_util::future::join_all(parsed.into_iter().enumerate().map(
+        |(index, request)| {
+            let state = &state;
+            let unavailable = &unavailable;
+            async move {
+                let outcome = match request {
+                    Ok(request) => {
+                        let prefetch_error = root_programs(request.authorizations())
+                            .into_iter()
+                            .find_map(|program_id| unavailable.get(&program_id));
+                        match prefetch_error {
+                            Some(err) => Err(ProveFailure::Internal(err.clone())),
+                            None => {
+                                state
+                                    .service
+                                    .prove_parsed(request, CancellationToken::new(), |_| {})
+                                    .await
+                            }
+                        }
                     }
+                    Err(failure) => Err(failure),
                 };
-
-                if status.is_success() {
-                    info!(": Status {}", status);
-                } else {
-                    warn!("Broadcast returned error status: {}. Body: {}", status, body);
+                match outcome {
+                    Ok(body) => serde_json::json!({
+                        "index": index,
+                        "status": StatusCode::OK.as_u16(),
+                        "body": body,
+                    }),
+                    Err(failure) => serde_json::json!({
+                        "index": index,
+                        "status": failure_status(&failure).as_u16(),
+                        "message": failure.message(),
+                    }),
                 }
+            }
+        },
+    ))
+    .await;
 
-                serde_json::json!({
-                    "requested": true,
-                    "endpoint": endpoint,
-                    "status": status.as_u16(),
-                    "success": status.is_success(),
-                    "response": body,
-                    "payload_preview": transaction_preview,
-                })
+    let succeeded = results
+        .iter()
+        .filter(|result| result["status"] == StatusCode::OK.as_u16())
+        .count();
+    info!(
+        "Batch finished: {} of {} requests succeeded.",
+        succeeded,
+        results.len()
+    );
+
+    Ok(json_reply(
+        StatusCode::OK,
+        serde_json::json!({
+            "status": "success",
+            "network": NETWORK,
+            "succeeded": succeeded,
+            "failed": results.len() - succeeded,
+            "results": results,
+        }),
+    ))
+}
+
+async fn handle_prove_socket(socket: WebSocket, state: ProverState) {
+    let (mut sink, mut stream) = socket.split();
+    let (tx, mut rx) = mpsc::channel::<serde_json::Value>(SOCKET_OUTBOX);
+
+    let writer = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            if sink.send(Message::text(message.to_string())).await.is_err() {
+                break;
             }
+        }
+        let _ = sink.close().await;
+    });
+
+    let mut next_id: u64 = 0;
+    let session = CancellationToken::new();
+    let in_flight = Arc::new(Semaphore::new(SOCKET_MAX_IN_FLIGHT));
+    info!("WebSocket proving session opened.");
+
+    while let Some(frame) = stream.next().await {
+        let message = match frame {
+            Ok(message) => message,
             Err(err) => {
-                error!("Broadcast request failed: {}", err);
-                serde_json::json!({
-                    "requested": true,
-                    "endpoint": endpoint,
-                    "success": false,
-                    "error": err.to_string(),
-                    "payload_preview": transaction_preview,
-                })
+                warn!("WebSocket receive error: {}", err);
+                break;
             }
         };
 
-        if let Some(object) = response_json.as_object_mut() {
-            object.insert("broadcast".to_string(), broadcast_meta);
+        if message.is_close() {
+            break;
         }
-    } else if let Some(object) = response_json.as_object_mut() {
-        object.insert(
-            "broadcast".to_string(),
-            serde_json::json!({
-                "requested": false,
-            }),
-        );
-        info!("Broadcast skipped (not requested).");
+        if message.is_ping() || message.is_pong() {
+            continue;
+        }
+        let Ok(text) = message.to_str() else {
+            let _ = tx
+                .send(socket_error(
+                    serde_json::Value::Null,
+                    StatusCode::BAD_REQUEST,
+                    "Only text messages carrying a JSON ProveRequest are accepted",
+                ))
+                .await;
+            continue;
+        };
+
+        let SocketProveRequest { id, request } = match serde_json::from_str(text) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                warn!("Invalid WebSocket proving message: {}", err);
+                let _ = tx
+                    .send(socket_error(
+                        serde_json::Value::Null,
+                        StatusCode::BAD_REQUEST,
+                        format!("Invalid request message: {err}"),
+                    ))
+                    .await;
+                continue;
+            }
+        };
+        let id = id.unwrap_or_else(|| next_id.to_string());
+        next_id += 1;
+
+        let Ok(permit) = in_flight.clone().try_acquire_owned() else {
+            let _ = tx
+                .send(socket_error(
+                    id.into(),
+                    StatusCode::TOO_MANY_REQUESTS,
+                    format!(
+                        "At most {SOCKET_MAX_IN_FLIGHT} requests may be in flight per connection"
+                    ),
+                ))
+                .await;
+            continue;
+        };
+        let _ = tx
+            .send(serde_json::json!({ "type": "accepted", "id": id }))
+            .await;
+
+        let tx = tx.clone();
+        let state = state.clone();
+        let cancel = session.child_token();
+        tokio::spawn(async move {
+            let _permit = permit;
+            // Progress is advisory, so it is dropped rather than waited on when the client
+            // falls behind.
+            let progress = |stage: ProveStage| {
+                let _ = tx.try_send(serde_json::json!({
+                    "type": "progress",
+                    "id": id,
+                    "stage": stage.as_str(),
+                }));
+            };
+
+            let message = match state.service.prove_with(&request, cancel, progress).await {
+                Ok(body) => serde_json::json!({
+                    "type": "result",
+                    "id": id,
+                    "status": StatusCode::OK.as_u16(),
+                    "body": body,
+                }),
+                Err(failure) => {
+                    socket_error(id.as_str().into(), failure_status(&failure), failure.message())
+                }
+            };
+            let _ = tx.send(message).await;
+        });
     }
 
-    Ok(json_reply(StatusCode::OK, response_json))
+    // Requests still in flight have nobody left to report to.
+    session.cancel();
+    drop(tx);
+    info!("WebSocket proving session closed.");
+    let _ = writer.await;
 }
 
-fn parse_authorization(
-    label: &str,
-    payload: &serde_json::Value,
-) -> Result<Authorization<CurrentNetwork>, String> {
-    let json = serde_json::to_string(payload)
-        .map_err(|err| format!("Invalid {label} payload: {err}"))?;
-    Authorization::<CurrentNetwork>::from_str(&json)
-        .map_err(|err| format!("Error parsing {label}: {err}"))
+/// Whether an `Authorization` header carries `Bearer <token>`, compared in constant time.
+fn bearer_matches(header: Option<&str>, token: &str) -> bool {
+    let Some(presented) = header.and_then(|header| header.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let (presented, token) = (presented.trim().as_bytes(), token.as_bytes());
+    presented.len() == token.len()
+        && presented
+            .iter()
+            .zip(token)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn socket_error(
+    id: serde_json::Value,
+    status: StatusCode,
+    message: impl Into<String>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "type": "error",
+        "id": id,
+        "status": status.as_u16(),
+        "message": message.into(),
+    })
 }
 
 fn json_reply(
     status: StatusCode,
-    body: serde_json::Value,
+    body: impl serde::Serialize,
 ) -> warp::reply::WithStatus<warp::reply::Json> {
     warp::reply::with_status(warp::reply::json(&body), status)
 }
 
-fn error_reply(message: impl Into<String>) -> warp::reply::WithStatus<warp::reply::Json> {
+fn bad_request(message: impl Into<String>) -> warp::reply::WithStatus<warp::reply::Json> {
     json_reply(
-        StatusCode::INTERNAL_SERVER_ERROR,
+        StatusCode::BAD_REQUEST,
         serde_json::json!({ "status": "error", "message": message.into() }),
     )
 }
 
-fn bad_request(message: impl Into<String>) -> warp::reply::WithStatus<warp::reply::Json> {
+fn not_found(message: impl Into<String>) -> warp::reply::WithStatus<warp::reply::Json> {
     json_reply(
-        StatusCode::BAD_REQUEST,
+        StatusCode::NOT_FOUND,
         serde_json::json!({ "status": "error", "message": message.into() }),
     )
 }
-
-fn truncate_for_log(input: &str, max_len: usize) -> String {
-    if input.chars().count() <= max_len {
-        return input.to_owned();
-    }
-
-    let mut truncated: String = input.chars().take(max_len).collect();
-    truncated.push('…');
-    truncated
-}
@@ -0,0 +1,818 @@
+//! Transport-independent proving API.
+//!
+//! [`ProverService`] owns the shared [`Process`], the proving pool and the program resolver.
+//! The warp routes in [`crate::prover_routes`] are a thin adapter over it, and other HTTP
+//! stacks can embed it directly.
+
+use crate::{
+    config::ProverConfig,
+    keys::KeyCache,
+    model::{BroadcastResult, ProveRequest, ProveResponse, UpstreamHealth},
+    programs::{
+        ensure_programs_available, is_credits, referenced_programs, replace_programs,
+        root_programs, EditionChecks, ProgramCache, ProgramLease,
+    },
+    proving::{decrypt_records, prove_transaction},
+    resolver::{ProgramResolver, RestSource},
+    upstream::CircuitBreaker,
+    workers::WorkerPool,
+    CurrentNetwork, NETWORK,
+};
+use parking_lot::RwLock;
+use snarkvm::{
+    prelude::{Authorization, ProgramID, ViewKey},
+    synthesizer::Process,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{Mutex, RwLock as AsyncRwLock, Semaphore};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+/// Milestones reported to callers while a request is processed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProveStage {
+    Parsed,
+    ProgramsReady,
+    Proving,
+    Proved,
+    Broadcasting,
+}
+
+impl ProveStage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProveStage::Parsed => "parsed",
+            ProveStage::ProgramsReady => "programs_ready",
+            ProveStage::Proving => "proving",
+            ProveStage::Proved => "proved",
+            ProveStage::Broadcasting => "broadcasting",
+        }
+    }
+}
+
+/// Why a request did not produce a transaction.
+#[derive(Debug)]
+pub enum ProveFailure {
+    /// The request itself is invalid.
+    BadRequest(String),
+    /// The request is valid but proving it failed.
+    Internal(String),
+    Cancelled,
+    TimedOut(Duration),
+}
+
+impl ProveFailure {
+    pub fn message(&self) -> String {
+        match self {
+            ProveFailure::BadRequest(message) | ProveFailure::Internal(message) => {
+                message.clone()
+            }
+            ProveFailure::Cancelled => "Proving job was cancelled".to_string(),
+            ProveFailure::TimedOut(deadline) => format!(
+                "Proving job exceeded its deadline of {} seconds",
+                deadline.as_secs()
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ProveFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for ProveFailure {}
+
+/// A request whose authorization payloads have been decoded.
+pub(crate) struct ParsedProveRequest {
+    authorization: Authorization<CurrentNetwork>,
+    fee_authorization: Option<Authorization<CurrentNetwork>>,
+    broadcast: bool,
+    view_key: Option<ViewKey<CurrentNetwork>>,
+    expected_editions: HashMap<ProgramID<CurrentNetwork>, u16>,
+}
+
+impl ParsedProveRequest {
+    pub(crate) fn parse(req: &ProveRequest) -> Result<Self, ProveFailure> {
+        info!(
+            "Received proving request. Broadcast requested: {:?}",
+            req.broadcast.unwrap_or(true)
+        );
+
+        let authorization = match parse_authorization("authorization", &req.authorization) {
+            Ok(auth) => auth,
+            Err(err) => {
+                warn!("Invalid authorization payload: {}", err);
+                return Err(ProveFailure::BadRequest(err));
+            }
+        };
+        debug!("Authorization payload parsed successfully.");
+
+        let fee_authorization = match req.fee_authorization.as_ref() {
+            Some(payload) => match parse_authorization("fee_authorization", payload) {
+                Ok(auth) => Some(auth),
+                Err(err) => {
+                    warn!("Invalid fee authorization payload: {}", err);
+                    return Err(ProveFailure::BadRequest(err));
+                }
+            },
+            None => None,
+        };
+        if fee_authorization.is_some() {
+            debug!("Fee authorization payload parsed successfully.");
+        }
+
+        let view_key = req
+            .view_key
+            .as_deref()
+            .map(|view_key| ViewKey::<CurrentNetwork>::from_str(view_key.trim()))
+            .transpose()
+            .map_err(|err| ProveFailure::BadRequest(format!("Error parsing view_key: {err}")))?;
+
+        let expected_editions = req
+            .program_editions
+            .iter()
+            .flatten()
+            .map(|(program_id, edition)| {
+                ProgramID::<CurrentNetwork>::from_str(program_id)
+                    .map(|program_id| (program_id, *edition))
+                    .map_err(|err| {
+                        ProveFailure::BadRequest(format!(
+                            "Error parsing program_editions entry '{program_id}': {err}"
+                        ))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            authorization,
+            fee_authorization,
+            broadcast: req.broadcast.unwrap_or(true),
+            view_key,
+            expected_editions,
+        })
+    }
+
+    pub(crate) fn authorizations(&self) -> impl Iterator<Item = &Authorization<CurrentNetwork>> {
+        std::iter::once(&self.authorization).chain(self.fee_authorization.as_ref())
+    }
+}
+
+/// Proves authorizations against a shared [`Process`], limited to the configured number of
+/// concurrent proofs.
+pub struct ProverService {
+    process: Arc<RwLock<Process<CurrentNetwork>>>,
+    config: Arc<ProverConfig>,
+    proving_slots: Arc<Semaphore>,
+    workers: Option<WorkerPool>,
+    resolver: ProgramResolver<CurrentNetwork>,
+    explorer_breaker: Arc<CircuitBreaker>,
+    key_cache: Option<KeyCache>,
+    programs: Arc<ProgramCache>,
+    editions: EditionChecks,
+    /// Held while the shared process is rebuilt, for eviction or an edition upgrade.
+    rebuilding: Mutex<()>,
+    /// Shared while a request adds programs to the process and leases them, exclusive while a
+    /// rebuilt process is swapped in, so a rebuild never drops a program that was loaded for
+    /// a request but not leased yet.
+    loading: AsyncRwLock<()>,
+}
+
+impl ProverService {
+    pub fn new(process: Arc<RwLock<Process<CurrentNetwork>>>, config: Arc<ProverConfig>) -> Self {
+        let proving_slots = Arc::new(Semaphore::new(config.max_concurrent_proofs()));
+        let key_cache = config.key_cache_dir().map(KeyCache::new);
+        let workers = config.worker_executable().map(|executable| {
+            WorkerPool::new(
+                executable.to_path_buf(),
+                config.max_concurrent_proofs(),
+                key_cache.clone(),
+                config.max_loaded_programs(),
+                config.max_loaded_keys(),
+                config.parallel_fee(),
+            )
+        });
+        let pinned = config
+            .pinned_programs()
+            .iter()
+            .filter_map(|program_id| match ProgramID::<CurrentNetwork>::from_str(program_id) {
+                Ok(program_id) => Some(program_id),
+                Err(err) => {
+                    warn!("Ignoring invalid pinned program '{}': {}", program_id, err);
+                    None
+                }
+            });
+        let programs = Arc::new(ProgramCache::new(
+            config.max_loaded_programs(),
+            config.max_loaded_keys(),
+            pinned,
+        ));
+        let editions = EditionChecks::new(config.edition_check_interval());
+        let explorer_breaker = Arc::new(CircuitBreaker::new(
+            config.circuit_breaker_threshold(),
+            config.circuit_breaker_cooldown(),
+        ));
+        let rest_source = RestSource::new(
+            config.http_client().clone(),
+            &ProverConfig::network_api_base(),
+        )
+        .expect("network API base is a valid URL")
+        .with_retry_policy(config.retry_policy())
+        .with_circuit_breaker(explorer_breaker.clone());
+
+        Self {
+            process,
+            config,
+            proving_slots,
+            workers,
+            resolver: ProgramResolver::new().with_source(rest_source),
+            explorer_breaker,
+            key_cache,
+            programs,
+            editions,
+            rebuilding: Mutex::new(()),
+            loading: AsyncRwLock::new(()),
+        }
+    }
+
+    /// Resolves missing programs with `resolver` instead of the network's explorer API.
+    pub fn with_resolver(mut self, resolver: ProgramResolver<CurrentNetwork>) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    pub fn process(&self) -> &Arc<RwLock<Process<CurrentNetwork>>> {
+        &self.process
+    }
+
+    pub fn config(&self) -> &ProverConfig {
+        &self.config
+    }
+
+    /// The state of the circuit breaker guarding program and edition fetches from the
+    /// explorer API.
+    pub fn explorer_health(&self) -> UpstreamHealth {
+        self.explorer_breaker.health()
+    }
+
+    /// The on-disk key cache, if [`ProverConfig::key_cache_dir`] is set.
+    pub fn key_cache(&self) -> Option<&KeyCache> {
+        self.key_cache.as_ref()
+    }
+
+    /// Deletes cached keys for `program_id`, or for every program when it is `None`, on the
+    /// blocking thread pool. Keys already loaded into the process stay in use until it is
+    /// restarted.
+    pub async fn purge_keys(
+        &self,
+        program_id: Option<ProgramID<CurrentNetwork>>,
+    ) -> Result<usize, String> {
+        let Some(key_cache) = self.key_cache.clone() else {
+            return Err("The proving-key cache is disabled".to_string());
+        };
+        tokio::task::spawn_blocking(move || key_cache.purge(program_id.as_ref()))
+            .await
+            .map_err(|err| format!("Purging cached keys panicked: {err}"))?
+    }
+
+    /// Proves `request` and, if it asks for it, broadcasts the transaction.
+    pub async fn prove(&self, request: &ProveRequest) -> Result<ProveResponse, ProveFailure> {
+        self.prove_with(request, CancellationToken::new(), |_| {}).await
+    }
+
+    /// Like [`ProverService::prove`], reporting each [`ProveStage`] to `progress` and giving
+    /// up once `cancel` fires, which frees the proving slot at once. Without worker
+    /// processes, a proof already running keeps using the CPU until its current snarkVM
+    /// stage finishes.
+    pub async fn prove_with<F>(
+        &self,
+        request: &ProveRequest,
+        cancel: CancellationToken,
+        progress: F,
+    ) -> Result<ProveResponse, ProveFailure>
+    where
+        F: Fn(ProveStage) + Send + Sync,
+    {
+        let request = ParsedProveRequest::parse(request)?;
+        self.prove_parsed(request, cancel, progress).await
+    }
+
+    /// Checks that `request` can be proven here: its payloads decode, every program it calls
+    /// can be loaded, and each call matches a function of the loaded program.
+    pub async fn validate(&self, request: &ProveRequest) -> Result<(), ProveFailure> {
+        let request = ParsedProveRequest::parse(request)?;
+        self.load_programs(request.authorizations(), &request.expected_editions)
+            .await
+            .map_err(ProveFailure::Internal)?;
+
+        let process = self.process.read();
+        for authorization in request.authorizations() {
+            for call in authorization.to_vec_deque() {
+                let stack = process.get_stack(*call.program_id()).map_err(|err| {
+                    ProveFailure::BadRequest(format!(
+                        "Program '{}' is not loaded: {err}",
+                        call.program_id()
+                    ))
+                })?;
+                let function = stack
+                    .program()
+                    .get_function(call.function_name())
+                    .map_err(|err| ProveFailure::BadRequest(err.to_string()))?;
+                if function.inputs().len() != call.inputs().len() {
+                    return Err(ProveFailure::BadRequest(format!(
+                        "'{}/{}' takes {} input(s), but the request has {}",
+                        call.program_id(),
+                        call.function_name(),
+                        function.inputs().len(),
+                        call.inputs().len()
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes every program referenced by `authorizations` (and their imports) available,
+    /// reloading programs whose edition is due for a check and out of date.
+    pub async fn ensure_programs<'a>(
+        &self,
+        authorizations: impl IntoIterator<Item = &'a Authorization<CurrentNetwork>>,
+    ) -> Result<(), String> {
+        self.load_programs(authorizations, &HashMap::new()).await.map(drop)
+    }
+
+    /// Like [`ProverService::ensure_programs`], leasing the programs so eviction keeps them
+    /// until the returned lease is dropped. Programs loaded at another edition than
+    /// `expected_editions` names are checked for an upgrade right away.
+    async fn load_programs<'a>(
+        &self,
+        authorizations: impl IntoIterator<Item = &'a Authorization<CurrentNetwork>>,
+        expected_editions: &HashMap<ProgramID<CurrentNetwork>, u16>,
+    ) -> Result<ProgramLease<'_>, String> {
+        let authorizations: Vec<_> = authorizations.into_iter().collect();
+        let loading = self.loading.read().await;
+        ensure_programs_available(&self.process, &self.resolver, authorizations.iter().copied())
+            .await?;
+        let referenced = referenced_programs(&self.process.read(), authorizations.iter().copied());
+        let lease = self.programs.lease(referenced.clone());
+        drop(loading);
+
+        let mismatched: Vec<_> = {
+            let process = self.process.read();
+            referenced
+                .iter()
+                .filter(|program_id| !is_credits(program_id))
+                .filter(|program_id| {
+                    expected_editions.get(*program_id).is_some_and(|expected| {
+                        process
+                            .get_stack(**program_id)
+                            .is_ok_and(|stack| *stack.program_edition() != *expected)
+                    })
+                })
+                .copied()
+                .collect()
+        };
+        let mut due = self.editions.due(referenced);
+        due.retain(|program_id| !mismatched.contains(program_id));
+        due.extend(mismatched);
+        self.refresh_editions(due).await;
+        Ok(lease)
+    }
+
+    /// Makes the programs `authorizations` call available one root program at a time, so a
+    /// program that cannot be loaded only fails the requests calling it. Shared imports are
+    /// still fetched once. Returns why each failed root program could not be loaded.
+    pub(crate) async fn prefetch_programs<'a>(
+        &self,
+        authorizations: impl IntoIterator<Item = &'a Authorization<CurrentNetwork>>,
+    ) -> HashMap<ProgramID<CurrentNetwork>, String> {
+        let roots: HashSet<_> = root_programs(authorizations).into_iter().collect();
+        let _loading = self.loading.read().await;
+        let outcomes = futures_util::future::join_all(roots.into_iter().map(|root| async move {
+            let outcome = self.resolver.ensure_in_process(&self.process, [root]).await;
+            (root, outcome)
+        }))
+        .await;
+
+        outcomes
+            .into_iter()
+            .filter_map(|(root, outcome)| outcome.err().map(|err| (root, err)))
+            .collect()
+    }
+
+    /// Compares the loaded edition of each program in `programs` with the latest one and
+    /// reloads outdated programs along with the programs importing them. A reload that fails
+    /// is logged and leaves the loaded editions in use; the next request retries it.
+    async fn refresh_editions(&self, programs: Vec<ProgramID<CurrentNetwork>>) {
+        let mut stale = Vec::new();
+        for program_id in programs {
+            let latest = match self.resolver.latest_edition(&program_id).await {
+                Ok(Some(latest)) => latest,
+                Ok(None) => {
+                    self.editions.mark_checked(program_id);
+                    continue;
+                }
+                Err(err) => {
+                    warn!("Failed to check the edition of '{}': {}", program_id, err);
+                    continue;
+                }
+            };
+            let loaded = match self.process.read().get_stack(program_id) {
+                Ok(stack) => *stack.program_edition(),
+                Err(_) => continue,
+            };
+            if loaded == latest {
+                self.editions.mark_checked(program_id);
+                continue;
+            }
+            info!(
+                "Program '{}' is loaded at edition {} but edition {} is current",
+                program_id, loaded, latest
+            );
+            stale.push(program_id);
+        }
+        if stale.is_empty() {
+            return;
+        }
+
+        match self.reload_programs(&stale).await {
+            Ok(reloaded) => {
+                for program_id in stale {
+                    self.editions.mark_checked(program_id);
+                }
+                info!("Reloaded {} program(s) after an edition upgrade", reloaded);
+            }
+            Err(err) => warn!(
+                "Failed to reload outdated programs, keeping the loaded editions: {}",
+                err
+            ),
+        }
+    }
+
+    /// Fetches the latest editions of `stale` and swaps in a process rebuilt with them.
+    /// Returns how many programs were reloaded.
+    async fn reload_programs(&self, stale: &[ProgramID<CurrentNetwork>]) -> Result<usize, String> {
+        let _rebuilding = self.rebuilding.lock().await;
+        for program_id in stale {
+            self.resolver.invalidate(program_id);
+        }
+        // Imports the new editions add are fetched along; the programs importing a stale one
+        // are re-registered from the process by `replace_programs`.
+        let replacements = self
+            .resolver
+            .resolve_with_imports(stale.iter().copied(), |program_id| {
+                !stale.contains(program_id) && self.process.read().contains_program(program_id)
+            })
+            .await?;
+        let replaced: Vec<_> = replacements
+            .iter()
+            .map(|resolved| *resolved.program.id())
+            .collect();
+
+        let reloaded = async {
+            let fresh = tokio::task::spawn_blocking(Process::<CurrentNetwork>::load)
+                .await
+                .map_err(|err| format!("Loading a process panicked: {err}"))?
+                .map_err(|err| format!("Failed to load a process: {err}"))?;
+            let _swapping = self.loading.write().await;
+            let process = self.process.clone();
+            // Swapping waits for the process write lock behind running executions, so it
+            // blocks a thread of the blocking pool instead of one of the runtime.
+            tokio::task::spawn_blocking(move || replace_programs(&process, fresh, replacements))
+                .await
+                .map_err(|err| format!("Rebuilding the process panicked: {err}"))?
+        }
+        .await;
+        // Swapped in or not, the resolved programs are fetched again when next needed.
+        for program_id in &replaced {
+            self.resolver.invalidate(program_id);
+        }
+        reloaded
+    }
+
+    /// Broadcasts a transaction, given in its JSON form, to the network.
+    pub async fn broadcast(&self, transaction: &serde_json::Value) -> BroadcastResult {
+        let endpoint = ProverConfig::broadcast_endpoint();
+        let client = self.config.http_client();
+        let payload_preview = truncate_for_log(&transaction.to_string(), 256);
+        info!("Broadcasting transaction to {}", endpoint);
+
+        match client.post(&endpoint).json(transaction).send().await {
+            Ok(resp) => {
+                let status = resp.status();
+                let body = match resp.text().await {
+                    Ok(text) => truncate_for_log(&text, 256),
+                    Err(err) => {
+                        error!("Error reading broadcast response body: {}", err);
+                        format!("<error reading body: {err}>")
+                    }
+                };
+
+                if status.is_success() {
+                    info!(": Status {}", status);
+                } else {
+                    warn!("Broadcast returned error status: {}. Body: {}", status, body);
+                }
+
+                BroadcastResult {
+                    requested: true,
+                    endpoint: Some(endpoint),
+                    status: Some(status.as_u16()),
+                    success: Some(status.is_success()),
+                    response: Some(body),
+                    error: None,
+                    payload_preview: Some(payload_preview),
+                }
+            }
+            Err(err) => {
+                error!("Broadcast request failed: {}", err);
+                BroadcastResult {
+                    requested: true,
+                    endpoint: Some(endpoint),
+                    status: None,
+                    success: Some(false),
+                    response: None,
+                    error: Some(err.to_string()),
+                    payload_preview: Some(payload_preview),
+                }
+            }
+        }
+    }
+
+    /// Evicts the least recently used programs once the process holds more programs than
+    /// [`ProverConfig::max_loaded_programs`] or more proving keys than
+    /// [`ProverConfig::max_loaded_keys`]. Only one eviction runs at a time.
+    async fn enforce_program_budget(&self) {
+        if !self.programs.over_budget(&self.process.read()) {
+            return;
+        }
+        let Ok(_rebuilding) = self.rebuilding.try_lock() else {
+            return;
+        };
+
+        // Loading a process takes a while, so it happens before the write lock is taken.
+        let fresh = match tokio::task::spawn_blocking(Process::<CurrentNetwork>::load).await {
+            Ok(Ok(fresh)) => fresh,
+            Ok(Err(err)) => {
+                error!("Failed to load a process for eviction: {}", err);
+                return;
+            }
+            Err(join_error) => {
+                error!("Loading a process for eviction panicked: {}", join_error);
+                return;
+            }
+        };
+
+        let _swapping = self.loading.write().await;
+        let (programs, process) = (self.programs.clone(), self.process.clone());
+        // Like an edition reload, the swap waits for the write lock on the blocking pool.
+        match tokio::task::spawn_blocking(move || programs.evict(&process, fresh)).await {
+            Ok(Ok(evicted)) => {
+                for program_id in &evicted {
+                    debug!("Evicted program '{}'", program_id);
+                    self.resolver.invalidate(program_id);
+                }
+            }
+            Ok(Err(err)) => error!("Failed to evict programs: {}", err),
+            Err(join_error) => error!("Evicting programs panicked: {}", join_error),
+        }
+    }
+
+    /// Proves a request under the configured deadline, stopping early when `cancel` fires.
+    pub(crate) async fn prove_parsed<F>(
+        &self,
+        request: ParsedProveRequest,
+        cancel: CancellationToken,
+        progress: F,
+    ) -> Result<ProveResponse, ProveFailure>
+    where
+        F: Fn(ProveStage) + Send + Sync,
+    {
+        let work = self.prove_unbounded(request, cancel.clone(), progress);
+        let bounded = async {
+            match self.config.job_timeout() {
+                Some(deadline) => tokio::time::timeout(deadline, work)
+                    .await
+                    .unwrap_or_else(|_| {
+                        warn!("Proving job exceeded its {:?} deadline.", deadline);
+                        Err(ProveFailure::TimedOut(deadline))
+                    }),
+                None => work.await,
+            }
+        };
+
+        tokio::select! {
+            result = bounded => result,
+            _ = cancel.cancelled() => {
+                info!("Proving job cancelled.");
+                Err(ProveFailure::Cancelled)
+            }
+        }
+    }
+
+    async fn prove_unbounded<F>(
+        &self,
+        request: ParsedProveRequest,
+        cancel: CancellationToken,
+        progress: F,
+    ) -> Result<ProveResponse, ProveFailure>
+    where
+        F: Fn(ProveStage) + Send + Sync,
+    {
+        // Dropping this future before it completes (disconnect, deadline, cancellation)
+        // signals the blocking proving task to stop at its next checkpoint.
+        let _cancel_on_drop = cancel.clone().drop_guard();
+
+        progress(ProveStage::Parsed);
+
+        debug!("Ensuring programs are available locally...");
+        let _lease = match self
+            .load_programs(request.authorizations(), &request.expected_editions)
+            .await
+        {
+            Ok(lease) => lease,
+            Err(err) => {
+                error!("Failed to ensure programs available: {}", err);
+                return Err(ProveFailure::Internal(err));
+            }
+        };
+        self.enforce_program_budget().await;
+        progress(ProveStage::ProgramsReady);
+
+        let ParsedProveRequest {
+            authorization,
+            fee_authorization,
+            broadcast: broadcast_requested,
+            view_key,
+            ..
+        } = request;
+
+        let permit = self
+            .proving_slots
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| ProveFailure::Internal("Proving pool is shut down".to_string()))?;
+
+        info!("Starting proof generation...");
+        progress(ProveStage::Proving);
+
+        let endpoint = self.config.query_endpoint().to_string();
+
+        let key_cache = self.key_cache.clone();
+        let parallel_fee = self.config.parallel_fee();
+        let proving_result = match &self.workers {
+            Some(workers) => {
+                // If this future is dropped mid-proof the checked-out worker process is
+                // killed, so the slot and the CPU are released together.
+                let result = workers
+                    .prove(
+                        &self.process,
+                        &authorization,
+                        fee_authorization.as_ref(),
+                        endpoint,
+                    )
+                    .await;
+                drop(permit);
+                result
+            }
+            None => {
+                let process_for_exec = self.process.clone();
+
+                // The slot stays with this future, not with the blocking task: if the future
+                // is dropped mid-proof the slot is free for the next request at once, while
+                // the abandoned task stops at its next cancellation checkpoint.
+                let result = tokio::task::spawn_blocking(move || {
+                    prove_transaction(
+                        process_for_exec,
+                        authorization,
+                        fee_authorization,
+                        endpoint,
+                        key_cache.as_ref(),
+                        parallel_fee,
+                        &cancel,
+                    )
+                })
+                .await
+                .unwrap_or_else(|join_error| {
+                    error!("Worker panicked while proving: {}", join_error);
+                    Err(format!("Worker panicked while proving: {join_error}"))
+                });
+                drop(permit);
+                result
+            }
+        };
+
+        let artifacts = match proving_result {
+            Ok(artifacts) => {
+                info!(
+                    "Proof generation successful. Execution ID: {}",
+                    artifacts.execution_id
+                );
+                artifacts
+            }
+            Err(err) => {
+                error!("Proof generation failed: {}", err);
+                return Err(ProveFailure::Internal(err));
+            }
+        };
+        progress(ProveStage::Proved);
+
+        // The transaction is valid whether or not its records can be decrypted, so a failure
+        // here is reported next to it instead of discarding the proof.
+        let (records, records_error) = match view_key.as_ref() {
+            Some(view_key) => match decrypt_records(&artifacts.transaction, view_key) {
+                Ok(records) => (records, None),
+                Err(err) => {
+                    warn!("Failed to decrypt records: {}", err);
+                    (Vec::new(), Some(err))
+                }
+            },
+            None => (Vec::new(), None),
+        };
+
+        let transaction_id = artifacts.transaction.id().to_string();
+        info!("Transaction ID: {}", transaction_id);
+
+        let transaction_type = if artifacts.transaction.is_deploy() {
+            "deploy"
+        } else if artifacts.transaction.is_fee() {
+            "fee"
+        } else {
+            "execute"
+        };
+
+        let transaction_string = match serde_json::to_string(&artifacts.transaction) {
+            Ok(value) => value,
+            Err(err) => {
+                error!("Failed to serialize transaction: {}", err);
+                return Err(ProveFailure::Internal(format!(
+                    "Failed to serialize transaction: {err}"
+                )));
+            }
+        };
+
+        let transaction_value: serde_json::Value =
+            match serde_json::from_str(&transaction_string) {
+                Ok(value) => value,
+                Err(err) => {
+                    error!("Failed to parse transaction JSON: {}", err);
+                    return Err(ProveFailure::Internal(format!(
+                        "Failed to parse transaction JSON: {err}"
+                    )));
+                }
+            };
+
+        let broadcast = if broadcast_requested {
+            progress(ProveStage::Broadcasting);
+            info!("Broadcasting transaction {}", transaction_id);
+            self.broadcast(&transaction_value).await
+        } else {
+            info!("Broadcast skipped (not requested).");
+            BroadcastResult::default()
+        };
+
+        Ok(ProveResponse {
+            status: "success".to_string(),
+            network: NETWORK.to_string(),
+            transaction_id,
+            transaction_type: transaction_type.to_string(),
+            execution_id: artifacts.execution_id,
+            transaction: transaction_value,
+            transaction_payload: transaction_string,
+            summary: artifacts.summary,
+            fee: artifacts.fee_info,
+            broadcast,
+            records,
+            records_error,
+        })
+    }
+}
+
+fn parse_authorization(
+    label: &str,
+    payload: &serde_json::Value,
+) -> Result<Authorization<CurrentNetwork>, String> {
+    let json = serde_json::to_string(payload)
+        .map_err(|err| format!("Invalid {label} payload: {err}"))?;
+    Authorization::<CurrentNetwork>::from_str(&json)
+        .map_err(|err| format!("Error parsing {label}: {err}"))
+}
+
+fn truncate_for_log(input: &str, max_len: usize) -> String {
+    if input.chars().count() <= max_len {
+        return input.to_owned();
+    }
+
+    let mut truncated: String = input.chars().take(max_len).collect();
+    truncated.push('…');
+    truncated
+}
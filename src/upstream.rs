@@ -0,0 +1,197 @@
+//! Resilience for calls to the explorer API: retries with jittered backoff and a circuit
+//! breaker that fails fast while the API is down.
+
+use crate::model::{CircuitState, UpstreamHealth};
+use parking_lot::Mutex;
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response, StatusCode};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Longest `Retry-After` that is waited out; a rate-limited API asking for more fails the
+/// request instead of holding it.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// How often, and how patiently, failed requests are retried.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every further one.
+    pub base_delay: Duration,
+    /// Upper bound for the backoff. A `Retry-After` on `429` is honoured beyond it, up to a
+    /// minute.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retry number `retry` (starting at 0): exponential backoff with
+    /// "equal jitter", i.e. uniformly between half and all of the backoff.
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay);
+        let half = backoff / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+}
+
+/// Stops calling an upstream API after `threshold` consecutive failures. After `cooldown`,
+/// a single trial request is let through; its outcome closes or re-opens the circuit.
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether a request may be sent now. An open circuit answers with how long it stays
+    /// open.
+    fn allow(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock();
+        let Some(opened_at) = state.opened_at else {
+            return Ok(());
+        };
+
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.cooldown {
+            return Err(self.cooldown - elapsed);
+        }
+        // Let one trial through. Should it never report back, another one is let through a
+        // cooldown later.
+        state.opened_at = Some(Instant::now());
+        state.trial_in_flight = true;
+        Ok(())
+    }
+
+    fn record_success(&self) {
+        *self.state.lock() = BreakerState::default();
+    }
+
+    fn record_failure(&self) {
+        let mut state = self.state.lock();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.trial_in_flight || state.consecutive_failures >= self.threshold {
+            if state.opened_at.is_none() || state.trial_in_flight {
+                warn!(
+                    "Upstream API failed {} time(s) in a row, failing fast for {:?}",
+                    state.consecutive_failures, self.cooldown
+                );
+            }
+            state.opened_at = Some(Instant::now());
+            state.trial_in_flight = false;
+        }
+    }
+
+    /// The breaker's current state, as reported by the health endpoint.
+    pub fn health(&self) -> UpstreamHealth {
+        let state = self.state.lock();
+        let (state_name, retry_in_secs) = match state.opened_at {
+            None => (CircuitState::Closed, None),
+            Some(_) if state.trial_in_flight => (CircuitState::HalfOpen, None),
+            Some(opened_at) => match self.cooldown.checked_sub(opened_at.elapsed()) {
+                Some(remaining) if !remaining.is_zero() => {
+                    (CircuitState::Open, Some(remaining.as_secs()))
+                }
+                _ => (CircuitState::HalfOpen, None),
+            },
+        };
+
+        UpstreamHealth {
+            state: state_name,
+            consecutive_failures: state.consecutive_failures,
+            retry_in_secs,
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
+/// Sends the request built by `build`, retrying connection errors, timeouts, `429` and `5xx`
+/// answers according to `retry`. Every attempt except a `429` is reported to `breaker`, and no
+/// attempt is made while it is open. A rate-limited API is up, so it is waited out via
+/// `Retry-After` rather than counted as a failure.
+pub(crate) async fn send_with_retry(
+    breaker: &CircuitBreaker,
+    retry: &RetryPolicy,
+    what: &str,
+    build: impl Fn() -> RequestBuilder,
+) -> Result<Response, String> {
+    let mut attempt = 0;
+    loop {
+        if let Err(open_for) = breaker.allow() {
+            return Err(format!(
+                "{what}: upstream API is unavailable, not retrying for another {}s",
+                open_for.as_secs().max(1)
+            ));
+        }
+
+        let (error, retry_after) = match build().send().await {
+            Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok())
+                    .map(Duration::from_secs);
+                (format!("status {}", response.status()), retry_after)
+            }
+            Ok(response) if response.status().is_server_error() => {
+                breaker.record_failure();
+                (format!("status {}", response.status()), None)
+            }
+            Ok(response) => {
+                breaker.record_success();
+                return Ok(response);
+            }
+            Err(err) => {
+                breaker.record_failure();
+                (err.to_string(), None)
+            }
+        };
+
+        if attempt >= retry.max_retries {
+            return Err(format!("{what}: {error}"));
+        }
+        if let Some(retry_after) = retry_after.filter(|delay| *delay > MAX_RETRY_AFTER) {
+            return Err(format!(
+                "{what}: {error}, rate limited for {}s",
+                retry_after.as_secs()
+            ));
+        }
+        let delay = retry_after.unwrap_or_else(|| retry.delay(attempt));
+        warn!("{} failed ({}), retrying in {:?}", what, error, delay);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
@@ -0,0 +1,404 @@
+use crate::{
+    keys::KeyCache,
+    model::{FeeInfo, ProvingSummary},
+    programs::{is_credits, loaded_key_count},
+    proving::{prove_transaction, ProvingArtifacts},
+    CurrentNetwork,
+};
+use parking_lot::{Mutex, RwLock};
+use snarkvm::prelude::{Authorization, Program, ProgramID, Transaction};
+use snarkvm::synthesizer::Process;
+use std::{
+    collections::HashSet,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    process::Stdio,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{Child, ChildStdin, ChildStdout, Command},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Command-line argument that starts the prover executable as a proving worker.
+pub const WORKER_ARG: &str = "worker";
+
+/// A program the worker must load before proving, in dependency order.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct WorkerProgram {
+    source: String,
+    edition: u16,
+}
+
+/// A proving job sent to a worker as a single line of JSON on its stdin.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct WorkerJob {
+    programs: Vec<WorkerProgram>,
+    authorization: String,
+    fee_authorization: Option<String>,
+    endpoint: String,
+    #[serde(default)]
+    key_cache_dir: Option<PathBuf>,
+    #[serde(default)]
+    max_programs: Option<usize>,
+    #[serde(default)]
+    max_keys: Option<usize>,
+    #[serde(default)]
+    parallel_fee: bool,
+}
+
+/// The worker's answer to a job, written as a single line of JSON on its stdout.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum WorkerReply {
+    Success {
+        transaction: String,
+        execution_id: String,
+        summary: ProvingSummary,
+        fee_info: Option<FeeInfo>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl WorkerReply {
+    fn into_artifacts(self) -> Result<ProvingArtifacts, String> {
+        match self {
+            WorkerReply::Success {
+                transaction,
+                execution_id,
+                summary,
+                fee_info,
+            } => Ok(ProvingArtifacts {
+                summary,
+                transaction: Transaction::<CurrentNetwork>::from_str(&transaction)
+                    .map_err(|err| format!("Worker returned an invalid transaction: {err}"))?,
+                execution_id,
+                fee_info,
+            }),
+            WorkerReply::Error { message } => Err(message),
+        }
+    }
+}
+
+/// Runs the worker side of the IPC protocol: reads jobs from stdin and writes one reply per
+/// job to stdout until stdin is closed.
+pub fn run_worker() -> Result<(), String> {
+    let process = Process::<CurrentNetwork>::load()
+        .map_err(|err| format!("Failed to initialize snarkvm process: {err}"))?;
+    let process = Arc::new(RwLock::new(process));
+    info!("Proving worker ready (pid {}).", std::process::id());
+
+    let stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+
+    for line in stdin.lines() {
+        let line = line.map_err(|err| format!("Failed to read job: {err}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = match serde_json::from_str::<WorkerJob>(&line) {
+            Ok(job) => run_job(&process, job).unwrap_or_else(|message| WorkerReply::Error {
+                message,
+            }),
+            Err(err) => WorkerReply::Error {
+                message: format!("Malformed worker job: {err}"),
+            },
+        };
+
+        serde_json::to_writer(&mut stdout, &reply)
+            .map_err(|err| format!("Failed to write reply: {err}"))?;
+        stdout
+            .write_all(b"\n")
+            .and_then(|_| stdout.flush())
+            .map_err(|err| format!("Failed to write reply: {err}"))?;
+    }
+
+    Ok(())
+}
+
+fn run_job(
+    process: &Arc<RwLock<Process<CurrentNetwork>>>,
+    job: WorkerJob,
+) -> Result<WorkerReply, String> {
+    let programs = job
+        .programs
+        .iter()
+        .map(|WorkerProgram { source, edition }| {
+            Program::<CurrentNetwork>::from_str(source)
+                .map(|program| (program, *edition))
+                .map_err(|err| format!("Failed to parse program: {err}"))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    {
+        let mut guard = process.write();
+        // Workers have no requests in flight between jobs, so when a program was upgraded or
+        // the job would take them over budget they simply start over with a fresh process.
+        let outdated = programs.iter().any(|(program, edition)| {
+            guard
+                .get_stack(*program.id())
+                .is_ok_and(|stack| *stack.program_edition() != *edition)
+        });
+        let missing = programs
+            .iter()
+            .filter(|(program, _)| !guard.contains_program(program.id()))
+            .count();
+        let over_budget = job
+            .max_programs
+            .is_some_and(|max| guard.program_ids().count().saturating_sub(1) + missing > max)
+            || job.max_keys.is_some_and(|max| loaded_key_count(&guard) > max);
+        if outdated || over_budget {
+            info!("Proving worker is reloading its process.");
+            *guard = Process::<CurrentNetwork>::load()
+                .map_err(|err| format!("Failed to reload snarkvm process: {err}"))?;
+        }
+
+        for (program, edition) in &programs {
+            if !guard.contains_program(program.id()) {
+                guard
+                    .add_program_with_edition(program, *edition)
+                    .map_err(|err| {
+                        format!(
+                            "Failed to add program '{}' (edition {edition}): {err}",
+                            program.id()
+                        )
+                    })?;
+            }
+        }
+    }
+
+    let authorization = Authorization::<CurrentNetwork>::from_str(&job.authorization)
+        .map_err(|err| format!("Error parsing authorization: {err}"))?;
+    let fee_authorization = job
+        .fee_authorization
+        .map(|fee| Authorization::<CurrentNetwork>::from_str(&fee))
+        .transpose()
+        .map_err(|err| format!("Error parsing fee_authorization: {err}"))?;
+
+    let artifacts = prove_transaction(
+        process.clone(),
+        authorization,
+        fee_authorization,
+        job.endpoint,
+        job.key_cache_dir.map(KeyCache::new).as_ref(),
+        job.parallel_fee,
+        &CancellationToken::new(),
+    )?;
+
+    Ok(WorkerReply::Success {
+        transaction: artifacts.transaction.to_string(),
+        execution_id: artifacts.execution_id,
+        summary: artifacts.summary,
+        fee_info: artifacts.fee_info,
+    })
+}
+
+/// A child process running [`run_worker`].
+struct WorkerProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl WorkerProcess {
+    fn spawn(executable: &Path) -> Result<Self, String> {
+        let mut child = Command::new(executable)
+            .arg(WORKER_ARG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| {
+                format!(
+                    "Failed to spawn proving worker '{}': {err}",
+                    executable.display()
+                )
+            })?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| "Proving worker has no stdin".to_string())?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| "Proving worker has no stdout".to_string())?;
+        info!("Spawned proving worker (pid {:?}).", child.id());
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+        })
+    }
+
+    /// Sends one job and waits for its reply. An error means the worker is no longer usable.
+    async fn run(&mut self, job: &str) -> Result<WorkerReply, String> {
+        let write = async {
+            self.stdin.write_all(job.as_bytes()).await?;
+            self.stdin.write_all(b"\n").await?;
+            self.stdin.flush().await
+        };
+        if let Err(err) = write.await {
+            return Err(format!("failed to send job: {err}"));
+        }
+
+        match self.stdout.next_line().await {
+            Ok(Some(line)) => {
+                serde_json::from_str(&line).map_err(|err| format!("malformed reply: {err}"))
+            }
+            Ok(None) => Err(self.exit_description().await),
+            Err(err) => Err(format!("failed to read reply: {err}")),
+        }
+    }
+
+    async fn exit_description(&mut self) -> String {
+        match tokio::time::timeout(Duration::from_secs(1), self.child.wait()).await {
+            Ok(Ok(status)) => format!("worker exited with {status}"),
+            _ => "worker closed its output".to_string(),
+        }
+    }
+}
+
+/// Pool of proving worker processes. Crashed workers are replaced with fresh ones, and a
+/// worker dropped mid-job (e.g. on cancellation) is killed.
+pub(crate) struct WorkerPool {
+    executable: PathBuf,
+    idle: Mutex<Vec<WorkerProcess>>,
+    key_cache: Option<KeyCache>,
+    max_programs: Option<usize>,
+    max_keys: Option<usize>,
+    parallel_fee: bool,
+}
+
+impl WorkerPool {
+    pub(crate) fn new(
+        executable: PathBuf,
+        size: usize,
+        key_cache: Option<KeyCache>,
+        max_programs: Option<usize>,
+        max_keys: Option<usize>,
+        parallel_fee: bool,
+    ) -> Self {
+        let idle = (0..size)
+            .filter_map(|_| match WorkerProcess::spawn(&executable) {
+                Ok(worker) => Some(worker),
+                Err(err) => {
+                    error!("{}", err);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            executable,
+            idle: Mutex::new(idle),
+            key_cache,
+            max_programs,
+            max_keys,
+            parallel_fee,
+        }
+    }
+
+    pub(crate) async fn prove(
+        &self,
+        process: &Arc<RwLock<Process<CurrentNetwork>>>,
+        authorization: &Authorization<CurrentNetwork>,
+        fee_authorization: Option<&Authorization<CurrentNetwork>>,
+        endpoint: String,
+    ) -> Result<ProvingArtifacts, String> {
+        let authorizations: Vec<_> = std::iter::once(authorization)
+            .chain(fee_authorization)
+            .collect();
+        let job = WorkerJob {
+            programs: collect_programs(process, &authorizations)?,
+            authorization: authorization.to_string(),
+            fee_authorization: fee_authorization.map(ToString::to_string),
+            endpoint,
+            key_cache_dir: self
+                .key_cache
+                .as_ref()
+                .map(|key_cache| key_cache.dir().to_path_buf()),
+            max_programs: self.max_programs,
+            max_keys: self.max_keys,
+            parallel_fee: self.parallel_fee,
+        };
+        let job = serde_json::to_string(&job)
+            .map_err(|err| format!("Failed to encode worker job: {err}"))?;
+
+        let mut worker = self.checkout()?;
+        match worker.run(&job).await {
+            Ok(reply) => {
+                self.idle.lock().push(worker);
+                reply.into_artifacts()
+            }
+            Err(err) => {
+                warn!("Proving worker failed ({}), restarting it.", err);
+                drop(worker);
+                match WorkerProcess::spawn(&self.executable) {
+                    Ok(replacement) => self.idle.lock().push(replacement),
+                    Err(spawn_err) => error!("{}", spawn_err),
+                }
+                Err(format!("Proving worker crashed: {err}"))
+            }
+        }
+    }
+
+    fn checkout(&self) -> Result<WorkerProcess, String> {
+        if let Some(worker) = self.idle.lock().pop() {
+            return Ok(worker);
+        }
+        WorkerProcess::spawn(&self.executable)
+    }
+}
+
+/// Collects the non-builtin programs referenced by `authorizations`, imports first.
+fn collect_programs(
+    process: &RwLock<Process<CurrentNetwork>>,
+    authorizations: &[&Authorization<CurrentNetwork>],
+) -> Result<Vec<WorkerProgram>, String> {
+    let guard = process.read();
+    let mut visited = HashSet::new();
+    let mut ordered = Vec::new();
+
+    for authorization in authorizations {
+        for request in authorization.to_vec_deque() {
+            push_with_imports(&guard, *request.program_id(), &mut visited, &mut ordered)?;
+        }
+    }
+
+    Ok(ordered)
+}
+
+fn push_with_imports(
+    process: &Process<CurrentNetwork>,
+    program_id: ProgramID<CurrentNetwork>,
+    visited: &mut HashSet<ProgramID<CurrentNetwork>>,
+    ordered: &mut Vec<WorkerProgram>,
+) -> Result<(), String> {
+    if is_credits(&program_id) || !visited.insert(program_id) {
+        return Ok(());
+    }
+
+    let stack = process
+        .get_stack(program_id)
+        .map_err(|err| format!("Program '{program_id}' is not loaded: {err}"))?;
+    let program = stack.program();
+    for import_id in program.imports().keys() {
+        push_with_imports(process, *import_id, visited, ordered)?;
+    }
+
+    ordered.push(WorkerProgram {
+        source: program.to_string(),
+        edition: *stack.program_edition(),
+    });
+    Ok(())
+}
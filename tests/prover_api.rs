@@ -1,10 +1,23 @@
+use futures_util::future::BoxFuture;
 use parking_lot::RwLock;
-use std::{str::FromStr, sync::Arc};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-use remote_prover::{prover_routes, CurrentAleo, CurrentNetwork, ProveRequest, ProverConfig};
+use remote_prover::{
+    prover_routes, CircuitBreaker, CircuitState, ClientError, CurrentAleo, CurrentNetwork,
+    JobState, OutputKind, ProgramResolver, ProgramSource, ProveFailure, ProveRequest,
+    ProveResponse, ProverClient, ProverConfig, ProverService, ResolvedProgram, RestSource,
+    RetryPolicy,
+};
 use serde_json::Value;
 use snarkvm::{
-    prelude::{Identifier, PrivateKey, Program},
+    prelude::{Authorization, Identifier, PrivateKey, Program, ProgramID},
     synthesizer::Process,
 };
 use warp::http::StatusCode;
@@ -39,6 +52,7 @@ async fn healthcheck_root_returns_ok() {
 
     let json: Value = serde_json::from_slice(response.body()).expect("invalid JSON body");
     assert_eq!(json["status"], "ok");
+    assert_eq!(json["explorer"]["state"], "closed");
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -87,6 +101,8 @@ function add_public:
         authorization: authorization_value,
         broadcast: Some(false),
         fee_authorization: None,
+        view_key: None,
+        program_editions: None,
     };
 
     let config = Arc::new(
@@ -130,4 +146,884 @@ function add_public:
         json.get("fee").is_none(),
         "fee section should be absent for fee-less requests"
     );
+
+    let typed: ProveResponse =
+        serde_json::from_value(json).expect("response should match ProveResponse");
+    assert_eq!(typed.summary.transitions, 1);
+    assert_eq!(typed.summary.call_metrics.len(), 1);
+    assert!(typed.summary.timings.is_some(), "timings should be reported");
+
+    let output = typed.summary.outputs.first().expect("missing output");
+    assert_eq!(output.kind, OutputKind::Public);
+    assert_eq!(output.value.as_deref(), Some("12u32"));
+    assert_eq!(output.function, "add_public");
+}
+
+const ADD_PROGRAM_SOURCE: &str = r#"
+program contract_execution.aleo;
+
+function add_public:
+    input r0 as u32.public;
+    input r1 as u32.public;
+    add r0 r1 into r2;
+    output r2 as u32.public;
+"#;
+
+/// Loads a process containing the sample program and authorizes `add_public(a, b)`.
+fn sample_process_and_authorization(
+    inputs: [&str; 2],
+) -> (Arc<RwLock<Process<CurrentNetwork>>>, Value) {
+    let program = Program::<CurrentNetwork>::from_str(ADD_PROGRAM_SOURCE)
+        .expect("failed to parse sample program");
+    let mut process = Process::<CurrentNetwork>::load().expect("failed to load process");
+    process
+        .add_program(&program)
+        .expect("failed to add sample program");
+
+    let function_name =
+        Identifier::<CurrentNetwork>::from_str("add_public").expect("missing function name");
+    let mut rng = rand::thread_rng();
+    let private_key =
+        PrivateKey::<CurrentNetwork>::new(&mut rng).expect("failed to create private key");
+    let authorization = process
+        .authorize::<CurrentAleo, _>(
+            &private_key,
+            program.id(),
+            function_name,
+            inputs.into_iter(),
+            &mut rng,
+        )
+        .expect("failed to authorize execution");
+    let authorization_value = serde_json::from_str(&authorization.to_string())
+        .expect("authorization should be valid JSON");
+
+    (Arc::new(RwLock::new(process)), authorization_value)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn websocket_streams_progress_and_result() {
+    let (process, authorization) = sample_process_and_authorization(["2u32", "3u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let routes = prover_routes(process, config);
+
+    let mut client = warp::test::ws()
+        .path("/prove/ws")
+        .handshake(routes)
+        .await
+        .expect("websocket handshake failed");
+
+    let message = serde_json::json!({
+        "id": "job-1",
+        "authorization": authorization,
+        "broadcast": false,
+    });
+    client.send_text(message.to_string()).await;
+
+    let mut stages = Vec::new();
+    let result = loop {
+        let frame = client.recv().await.expect("websocket closed early");
+        let json: Value =
+            serde_json::from_str(frame.to_str().expect("expected text frame")).unwrap();
+        assert_eq!(json["id"], "job-1");
+        match json["type"].as_str() {
+            Some("accepted") => {}
+            Some("progress") => stages.push(json["stage"].as_str().unwrap().to_string()),
+            Some("result") => break json,
+            other => panic!("unexpected message type {other:?}: {json}"),
+        }
+    };
+
+    assert!(stages.iter().any(|stage| stage == "proving"));
+    assert_eq!(result["status"], 200);
+    assert_eq!(result["body"]["status"], "success");
+    assert_eq!(result["body"]["transaction_type"], "execute");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn websocket_rejects_binary_frames() {
+    let process = Arc::new(RwLock::new(
+        Process::<CurrentNetwork>::load().expect("failed to load process"),
+    ));
+    let routes = prover_routes(process, Arc::new(ProverConfig::default()));
+
+    let mut client = warp::test::ws()
+        .path("/prove/ws")
+        .handshake(routes)
+        .await
+        .expect("websocket handshake failed");
+
+    client.send(warp::ws::Message::binary(vec![1, 2, 3])).await;
+    let frame = client.recv().await.expect("websocket closed early");
+    let json: Value = serde_json::from_str(frame.to_str().expect("expected text frame")).unwrap();
+    assert_eq!(json["type"], "error");
+    assert_eq!(json["status"], 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn batch_reports_independent_results() {
+    let (process, authorization) = sample_process_and_authorization(["4u32", "6u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let routes = prover_routes(process, config);
+
+    let batch = vec![
+        ProveRequest {
+            authorization,
+            broadcast: Some(false),
+            fee_authorization: None,
+            view_key: None,
+            program_editions: None,
+        },
+        ProveRequest {
+            authorization: serde_json::json!({ "requests": "not-an-authorization" }),
+            broadcast: Some(false),
+            fee_authorization: None,
+            view_key: None,
+            program_editions: None,
+        },
+    ];
+
+    let response = warp::test::request()
+        .method("POST")
+        .path("/prove/batch")
+        .json(&batch)
+        .reply(&routes)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK, "unexpected status");
+
+    let json: Value = serde_json::from_slice(response.body()).expect("invalid JSON body");
+    assert_eq!(json["succeeded"], 1);
+    assert_eq!(json["failed"], 1);
+
+    let results = json["results"].as_array().expect("missing results");
+    assert_eq!(results[0]["index"], 0);
+    assert_eq!(results[0]["status"], 200);
+    assert_eq!(results[0]["body"]["transaction_type"], "execute");
+    assert_eq!(results[1]["index"], 1);
+    assert_eq!(results[1]["status"], 400);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn oversized_batch_is_rejected() {
+    let process = Arc::new(RwLock::new(
+        Process::<CurrentNetwork>::load().expect("failed to load process"),
+    ));
+    let config = Arc::new(ProverConfig::default().with_max_batch_size(1));
+    let routes = prover_routes(process, config);
+
+    let request = ProveRequest {
+        authorization: serde_json::json!({}),
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    let response = warp::test::request()
+        .method("POST")
+        .path("/prove/batch")
+        .json(&vec![request.clone(), request])
+        .reply(&routes)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn job_can_be_polled_until_it_succeeds() {
+    let (process, authorization) = sample_process_and_authorization(["8u32", "9u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let routes = prover_routes(process, config);
+
+    let request_body = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    let response = warp::test::request()
+        .method("POST")
+        .path("/jobs")
+        .json(&request_body)
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED, "unexpected status");
+
+    let json: Value = serde_json::from_slice(response.body()).expect("invalid JSON body");
+    let job_id = json["job_id"].as_str().expect("missing job id").to_string();
+
+    let snapshot = loop {
+        let response = warp::test::request()
+            .method("GET")
+            .path(&format!("/jobs/{job_id}"))
+            .reply(&routes)
+            .await;
+        assert_eq!(response.status(), StatusCode::OK, "unexpected status");
+        let snapshot: Value = serde_json::from_slice(response.body()).unwrap();
+        match snapshot["state"].as_str() {
+            Some("queued") | Some("running") => {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await
+            }
+            _ => break snapshot,
+        }
+    };
+
+    assert_eq!(snapshot["state"], "succeeded", "job failed: {snapshot}");
+    assert_eq!(snapshot["result"]["transaction_type"], "execute");
+
+    let response = warp::test::request()
+        .method("DELETE")
+        .path("/jobs/unknown")
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancelled_job_reports_cancelled_state() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "1u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let routes = prover_routes(process, config);
+
+    let request_body = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    let response = warp::test::request()
+        .method("POST")
+        .path("/jobs")
+        .json(&request_body)
+        .reply(&routes)
+        .await;
+    let json: Value = serde_json::from_slice(response.body()).expect("invalid JSON body");
+    let job_id = json["job_id"].as_str().expect("missing job id").to_string();
+
+    let response = warp::test::request()
+        .method("DELETE")
+        .path(&format!("/jobs/{job_id}"))
+        .reply(&routes)
+        .await;
+    assert_eq!(response.status(), StatusCode::OK, "unexpected status");
+    let snapshot: Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(snapshot["state"], "cancelled");
+
+    // The job keeps its cancelled state once the proving task observes the cancellation.
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let response = warp::test::request()
+        .method("GET")
+        .path(&format!("/jobs/{job_id}"))
+        .reply(&routes)
+        .await;
+    let snapshot: Value = serde_json::from_slice(response.body()).unwrap();
+    assert_eq!(snapshot["state"], "cancelled");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn queued_jobs_are_capped() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "1u32"]);
+    let config = Arc::new(
+        ProverConfig::default()
+            .with_endpoint(static_query_payload())
+            .with_max_queued_jobs(1),
+    );
+    let routes = prover_routes(process, config);
+    let request_body = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    let submit = || {
+        warp::test::request()
+            .method("POST")
+            .path("/jobs")
+            .json(&request_body)
+            .reply(&routes)
+    };
+
+    let accepted = submit().await;
+    assert_eq!(accepted.status(), StatusCode::ACCEPTED, "unexpected status");
+    assert_eq!(submit().await.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let json: Value = serde_json::from_slice(accepted.body()).expect("invalid JSON body");
+    let job_id = json["job_id"].as_str().expect("missing job id");
+    warp::test::request()
+        .method("DELETE")
+        .path(&format!("/jobs/{job_id}"))
+        .reply(&routes)
+        .await;
+    assert_eq!(submit().await.status(), StatusCode::ACCEPTED, "a cancelled job frees its place");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn prove_in_worker_process() {
+    let (process, authorization) = sample_process_and_authorization(["10u32", "11u32"]);
+    let config = Arc::new(
+        ProverConfig::default()
+            .with_endpoint(static_query_payload())
+            .with_worker_executable(env!("CARGO_BIN_EXE_remote-prover")),
+    );
+    let routes = prover_routes(process, config);
+
+    let request_body = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    let response = warp::test::request()
+        .method("POST")
+        .path("/prove")
+        .json(&request_body)
+        .reply(&routes)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK, "unexpected status");
+
+    let json: Value = serde_json::from_slice(response.body()).expect("invalid JSON body");
+    assert_eq!(json["status"], "success");
+    assert_eq!(json["transaction_type"], "execute");
+    assert_eq!(json["summary"]["transitions"], 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn client_proves_through_job_api() {
+    let (process, authorization) = sample_process_and_authorization(["10u32", "11u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let (addr, server) =
+        warp::serve(prover_routes(process, config)).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let client = ProverClient::new(&format!("http://{addr}"))
+        .expect("valid prover URL")
+        .with_poll_interval(std::time::Duration::from_millis(200));
+    client.health().await.expect("prover should be healthy");
+
+    let request_body = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    let mut states = Vec::new();
+    let response = client
+        .prove_job(&request_body, |status| states.push(status.state))
+        .await
+        .expect("job should succeed");
+
+    assert_eq!(response.transaction_type, "execute");
+    assert!(!response.broadcast.requested);
+    assert_eq!(states.last(), Some(&JobState::Succeeded));
+
+    match client.job_status("unknown").await {
+        Err(ClientError::Api { status, .. }) => assert_eq!(status, 404),
+        other => panic!("expected a 404 API error, got {other:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn invalid_view_key_is_rejected() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "2u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let routes = prover_routes(process, config);
+
+    let request_body = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: Some("AViewKey1invalid".to_string()),
+        program_editions: None,
+    };
+    let response = warp::test::request()
+        .method("POST")
+        .path("/prove")
+        .json(&request_body)
+        .reply(&routes)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST, "unexpected status");
+    let json: Value = serde_json::from_slice(response.body()).expect("invalid JSON body");
+    assert!(
+        json["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("view_key")),
+        "unexpected error: {json}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn service_validates_and_proves_without_http() {
+    let (process, authorization) = sample_process_and_authorization(["5u32", "7u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let service = ProverService::new(process, config);
+
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    service.validate(&request).await.expect("request should validate");
+    let response = service.prove(&request).await.expect("proving should succeed");
+    assert_eq!(response.transaction_type, "execute");
+    assert!(!response.broadcast.requested);
+
+    let invalid = ProveRequest {
+        authorization: Value::String("not an authorization".to_string()),
+        ..request
+    };
+    assert!(matches!(service.validate(&invalid).await, Err(ProveFailure::BadRequest(_))));
+}
+
+/// Authorizes a public fee for `authorization`, paid by a fresh account.
+fn fee_authorization_for(
+    process: &RwLock<Process<CurrentNetwork>>,
+    authorization: &Value,
+) -> Value {
+    let authorization = Authorization::<CurrentNetwork>::from_str(&authorization.to_string())
+        .expect("failed to parse authorization");
+    let execution_id = authorization
+        .to_execution_id()
+        .expect("failed to compute execution ID");
+    let mut rng = rand::thread_rng();
+    let private_key =
+        PrivateKey::<CurrentNetwork>::new(&mut rng).expect("failed to create private key");
+    let fee = process
+        .read()
+        .authorize_fee_public::<CurrentAleo, _>(&private_key, 10_000, 0, execution_id, &mut rng)
+        .expect("failed to authorize fee");
+    serde_json::from_str(&fee.to_string()).expect("fee authorization should be valid JSON")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fee_is_proven_with_the_execution() {
+    let (process, authorization) = sample_process_and_authorization(["4u32", "5u32"]);
+    let fee_authorization = fee_authorization_for(&process, &authorization);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let service = ProverService::new(process, config);
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: Some(fee_authorization),
+        view_key: None,
+        program_editions: None,
+    };
+
+    let response = service.prove(&request).await.expect("proving should succeed");
+    assert_eq!(response.transaction_type, "execute");
+    let fee = response.fee.expect("the transaction should carry the fee");
+    assert_eq!(fee.kind, "public");
+    let timings = response.summary.timings.expect("timings should be reported");
+    assert!(timings.fee_ms.is_some(), "the fee should be timed");
+    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    assert_eq!(timings.fee_in_parallel, cores > 1);
+}
+
+/// Compares the latency of proving a fee after the execution and alongside it. Run with
+/// `cargo test --release -- --ignored fee_proving_latency --nocapture`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore = "measures proving latency"]
+async fn fee_proving_latency() {
+    let (process, authorization) = sample_process_and_authorization(["4u32", "5u32"]);
+    let fee_authorization = fee_authorization_for(&process, &authorization);
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: Some(fee_authorization),
+        view_key: None,
+        program_editions: None,
+    };
+
+    // The first proof synthesizes the keys, which would skew whichever mode ran first.
+    let config = ProverConfig::default().with_endpoint(static_query_payload());
+    let warm_up = ProverService::new(process.clone(), Arc::new(config.clone()));
+    warm_up.prove(&request).await.expect("proving should succeed");
+
+    for parallel in [false, true, false, true] {
+        let config = Arc::new(config.clone().with_parallel_fee(parallel));
+        let service = ProverService::new(process.clone(), config);
+        let response = service.prove(&request).await.expect("proving should succeed");
+        let timings = response.summary.timings.expect("timings should be reported");
+        eprintln!(
+            "fee in parallel: {:5}  total {:6} ms  execution {:6} ms  fee {:6?} ms",
+            timings.fee_in_parallel, timings.total_ms, timings.execution_ms, timings.fee_ms
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn proving_keys_are_cached_and_purged() {
+    let cache_dir = std::env::temp_dir().join(format!("remote-prover-keys-{}", std::process::id()));
+    let config = Arc::new(
+        ProverConfig::default()
+            .with_endpoint(static_query_payload())
+            .with_key_cache_dir(Some(cache_dir.clone())),
+    );
+
+    // The second run starts from a fresh process and picks the keys up from disk.
+    for inputs in [["1u32", "1u32"], ["2u32", "2u32"]] {
+        let (process, authorization) = sample_process_and_authorization(inputs);
+        let service = ProverService::new(process, config.clone());
+        let request = ProveRequest {
+            authorization,
+            broadcast: Some(false),
+            fee_authorization: None,
+            view_key: None,
+            program_editions: None,
+        };
+        service.prove(&request).await.expect("proving should succeed");
+    }
+
+    let (process, _) = sample_process_and_authorization(["3u32", "3u32"]);
+    let config = Arc::new((*config).clone().with_admin_token(Some("secret".to_string())));
+    let routes = prover_routes(process, config);
+    let unauthorized = warp::test::request()
+        .method("DELETE")
+        .path("/keys?program=contract_execution.aleo")
+        .reply(&routes)
+        .await;
+    assert_eq!(unauthorized.status(), StatusCode::UNAUTHORIZED);
+
+    let response = warp::test::request()
+        .method("DELETE")
+        .path("/keys?program=contract_execution.aleo")
+        .header("authorization", "Bearer secret")
+        .reply(&routes)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK, "unexpected status");
+    let json: Value = serde_json::from_slice(response.body()).expect("invalid JSON body");
+    assert_eq!(json["removed"], 1, "unexpected purge result: {json}");
+    let _ = std::fs::remove_dir_all(&cache_dir);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn least_recently_used_programs_are_evicted() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "2u32"]);
+    let idle_program = Program::<CurrentNetwork>::from_str(
+        "program idle_program.aleo;\n\nfunction echo:\n    input r0 as u32.public;\n    \
+         output r0 as u32.public;\n",
+    )
+    .expect("failed to parse idle program");
+    process
+        .write()
+        .add_program(&idle_program)
+        .expect("failed to add idle program");
+
+    let config = Arc::new(
+        ProverConfig::default()
+            .with_endpoint(static_query_payload())
+            .with_max_loaded_programs(Some(1)),
+    );
+    let service = ProverService::new(process, config);
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    service.prove(&request).await.expect("proving should succeed");
+
+    let process = service.process().read();
+    assert!(process.contains_program(&ProgramID::from_str("contract_execution.aleo").unwrap()));
+    assert!(!process.contains_program(idle_program.id()));
+    assert!(process.contains_program(&ProgramID::from_str("credits.aleo").unwrap()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn programs_over_the_key_budget_are_evicted() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "2u32"]);
+    let idle_program = Program::<CurrentNetwork>::from_str(
+        "program idle_program.aleo;\n\nfunction echo:\n    input r0 as u32.public;\n    \
+         output r0 as u32.public;\n",
+    )
+    .expect("failed to parse idle program");
+    let echo = Identifier::<CurrentNetwork>::from_str("echo").unwrap();
+    {
+        let mut process = process.write();
+        process
+            .add_program(&idle_program)
+            .expect("failed to add idle program");
+        process
+            .synthesize_key::<CurrentAleo, _>(idle_program.id(), &echo, &mut rand::thread_rng())
+            .expect("failed to synthesize idle program keys");
+    }
+
+    // Two programs fit the program budget, but the idle program's key and the one the
+    // request synthesizes do not fit the key budget together.
+    let config = Arc::new(
+        ProverConfig::default()
+            .with_endpoint(static_query_payload())
+            .with_max_loaded_programs(Some(2))
+            .with_max_loaded_keys(Some(1)),
+    );
+    let service = ProverService::new(process, config);
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    service.prove(&request).await.expect("proving should succeed");
+    service.prove(&request).await.expect("proving should succeed");
+
+    let process = service.process().read();
+    let program_id = ProgramID::<CurrentNetwork>::from_str("contract_execution.aleo").unwrap();
+    let add_public = Identifier::<CurrentNetwork>::from_str("add_public").unwrap();
+    assert!(!process.contains_program(idle_program.id()));
+    assert!(process
+        .get_stack(program_id)
+        .expect("program is loaded")
+        .contains_proving_key(&add_public));
+}
+
+/// Serves the sample program as if it had been upgraded to edition 1.
+struct UpgradedSource;
+
+impl ProgramSource<CurrentNetwork> for UpgradedSource {
+    fn describe(&self) -> String {
+        "upgraded".to_string()
+    }
+
+    fn load<'a>(
+        &'a self,
+        _program_id: &'a ProgramID<CurrentNetwork>,
+        _edition: Option<u16>,
+    ) -> BoxFuture<'a, Result<Option<ResolvedProgram<CurrentNetwork>>, String>> {
+        Box::pin(async {
+            let program = Program::from_str(ADD_PROGRAM_SOURCE).map_err(|err| err.to_string())?;
+            Ok(Some(ResolvedProgram {
+                program,
+                edition: 1,
+            }))
+        })
+    }
+
+    fn latest_edition<'a>(
+        &'a self,
+        _program_id: &'a ProgramID<CurrentNetwork>,
+    ) -> BoxFuture<'a, Result<Option<u16>, String>> {
+        Box::pin(async { Ok(Some(1)) })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn upgraded_programs_are_reloaded() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "2u32"]);
+    let config = Arc::new(
+        ProverConfig::default()
+            .with_endpoint(static_query_payload())
+            .with_edition_check_interval(Some(Duration::from_secs(60))),
+    );
+    let service = ProverService::new(process, config)
+        .with_resolver(ProgramResolver::new().with_source(UpgradedSource));
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+    service.validate(&request).await.expect("request should validate");
+
+    let program_id = ProgramID::<CurrentNetwork>::from_str("contract_execution.aleo").unwrap();
+    let stack = service.process().read().get_stack(program_id).expect("program is loaded");
+    assert_eq!(*stack.program_edition(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn programs_are_reloaded_at_the_expected_edition() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "2u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let service = ProverService::new(process, config)
+        .with_resolver(ProgramResolver::new().with_source(UpgradedSource));
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: Some([("contract_execution.aleo".to_string(), 1)].into()),
+    };
+    service.validate(&request).await.expect("request should validate");
+
+    let program_id = ProgramID::<CurrentNetwork>::from_str("contract_execution.aleo").unwrap();
+    let stack = service.process().read().get_stack(program_id).expect("program is loaded");
+    assert_eq!(*stack.program_edition(), 1);
+}
+
+/// Reports a new edition of every program but fails to serve it.
+struct UnavailableUpgradeSource;
+
+impl ProgramSource<CurrentNetwork> for UnavailableUpgradeSource {
+    fn describe(&self) -> String {
+        "unavailable upgrade".to_string()
+    }
+
+    fn load<'a>(
+        &'a self,
+        _program_id: &'a ProgramID<CurrentNetwork>,
+        _edition: Option<u16>,
+    ) -> BoxFuture<'a, Result<Option<ResolvedProgram<CurrentNetwork>>, String>> {
+        Box::pin(async { Err("explorer unavailable".to_string()) })
+    }
+
+    fn latest_edition<'a>(
+        &'a self,
+        _program_id: &'a ProgramID<CurrentNetwork>,
+    ) -> BoxFuture<'a, Result<Option<u16>, String>> {
+        Box::pin(async { Ok(Some(1)) })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_reload_keeps_the_loaded_edition() {
+    let (process, authorization) = sample_process_and_authorization(["1u32", "2u32"]);
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let service = ProverService::new(process, config)
+        .with_resolver(ProgramResolver::new().with_source(UnavailableUpgradeSource));
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: Some([("contract_execution.aleo".to_string(), 1)].into()),
+    };
+    service.prove(&request).await.expect("proving should succeed at the loaded edition");
+
+    let program_id = ProgramID::<CurrentNetwork>::from_str("contract_execution.aleo").unwrap();
+    let stack = service.process().read().get_stack(program_id).expect("program is loaded");
+    assert_eq!(*stack.program_edition(), 0);
+}
+
+/// Serves the sample program slowly and counts how often it was asked for it.
+struct CountingSource {
+    loads: Arc<AtomicUsize>,
+}
+
+impl ProgramSource<CurrentNetwork> for CountingSource {
+    fn describe(&self) -> String {
+        "counting".to_string()
+    }
+
+    fn load<'a>(
+        &'a self,
+        _program_id: &'a ProgramID<CurrentNetwork>,
+        _edition: Option<u16>,
+    ) -> BoxFuture<'a, Result<Option<ResolvedProgram<CurrentNetwork>>, String>> {
+        Box::pin(async {
+            self.loads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let program = Program::from_str(ADD_PROGRAM_SOURCE).map_err(|err| err.to_string())?;
+            Ok(Some(ResolvedProgram {
+                program,
+                edition: 0,
+            }))
+        })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn concurrent_requests_share_one_program_fetch() {
+    let (_, authorization) = sample_process_and_authorization(["1u32", "2u32"]);
+    let process = Arc::new(RwLock::new(
+        Process::<CurrentNetwork>::load().expect("failed to load process"),
+    ));
+    let config = Arc::new(ProverConfig::default().with_endpoint(static_query_payload()));
+    let loads = Arc::new(AtomicUsize::new(0));
+    let resolver = ProgramResolver::new().with_source(CountingSource {
+        loads: loads.clone(),
+    });
+    let service = ProverService::new(process, config).with_resolver(resolver);
+    let request = ProveRequest {
+        authorization,
+        broadcast: Some(false),
+        fee_authorization: None,
+        view_key: None,
+        program_editions: None,
+    };
+
+    let results =
+        futures_util::future::join_all((0..10).map(|_| service.validate(&request))).await;
+
+    assert!(results.iter().all(Result::is_ok), "unexpected results: {results:?}");
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failing_explorer_is_retried_then_short_circuited() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let unavailable = warp::any().map(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        warp::reply::with_status("unavailable", StatusCode::SERVICE_UNAVAILABLE)
+    });
+    let (addr, server) = warp::serve(unavailable).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let breaker = Arc::new(CircuitBreaker::new(3, Duration::from_secs(60)));
+    let source = RestSource::new(reqwest::Client::new(), &format!("http://{addr}"))
+        .expect("valid API base")
+        .with_retry_policy(RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        })
+        .with_circuit_breaker(breaker.clone());
+    let resolver = ProgramResolver::<CurrentNetwork>::new().with_source(source);
+    let program_id = ProgramID::<CurrentNetwork>::from_str("contract_execution.aleo").unwrap();
+
+    assert!(resolver.resolve(&program_id).await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 3, "one attempt plus two retries");
+    assert_eq!(breaker.health().state, CircuitState::Open);
+
+    let err = resolver.resolve(&program_id).await.expect_err("circuit should be open");
+    assert!(err.contains("unavailable"), "unexpected error: {err}");
+    assert_eq!(hits.load(Ordering::SeqCst), 3, "an open circuit must not reach the API");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn retry_after_is_honoured_up_to_a_ceiling() {
+    use warp::Reply;
+
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let rate_limited = warp::any().map(move || {
+        let retry_after = match counter.fetch_add(1, Ordering::SeqCst) {
+            0 => "1",
+            1 => return StatusCode::SERVICE_UNAVAILABLE.into_response(),
+            _ => "3600",
+        };
+        let reply = warp::reply::with_status("slow down", StatusCode::TOO_MANY_REQUESTS);
+        warp::reply::with_header(reply, "retry-after", retry_after).into_response()
+    });
+    let (addr, server) = warp::serve(rate_limited).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let source = RestSource::new(reqwest::Client::new(), &format!("http://{addr}"))
+        .expect("valid API base")
+        .with_retry_policy(RetryPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        });
+    let resolver = ProgramResolver::<CurrentNetwork>::new().with_source(source);
+    let program_id = ProgramID::<CurrentNetwork>::from_str("contract_execution.aleo").unwrap();
+
+    let started = std::time::Instant::now();
+    assert!(resolver.resolve(&program_id).await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1), "Retry-After was cut to max_delay");
+
+    let err = resolver.resolve(&program_id).await.expect_err("rate limited for an hour");
+    assert!(err.contains("rate limited for 3600s"), "unexpected error: {err}");
+    assert_eq!(hits.load(Ordering::SeqCst), 3, "an hour-long Retry-After is not waited out");
 }